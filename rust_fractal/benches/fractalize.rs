@@ -0,0 +1,81 @@
+//! Throughput of the chaos-game loop in `fractalize`.
+//!
+//! Run with `cargo bench`. Every benchmark reports points per second, where a
+//! point is one unit of `max_points`.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+
+use rust_fractal::{Accumulate, Evaluation, FractalMethod, Fractalize, FractalizeParameters, Histogram, MyColorImage, TrigMode};
+
+const POINTS: u32 = 1 << 20;
+
+fn params(method: FractalMethod, evaluation: Evaluation) -> FractalizeParameters
+{
+    FractalizeParameters::default()
+    .with_method(method)
+    .with_evaluation(evaluation)
+    .with_max_points(POINTS)
+    .with_seed(Some(0))
+}
+
+/// Renders into a fresh grid each iteration, so saturation never skews the numbers.
+fn bench_render<G: Accumulate>(c: &mut Criterion, group: &str, new_grid: impl Fn(u32) -> G)
+{
+    let mut group = c.benchmark_group(group);
+    group.throughput(Throughput::Elements(POINTS as u64));
+
+    for size in [256, 1024, 4096]
+    {
+        for method in [FractalMethod::Default, FractalMethod::MultiplyTheta]
+        {
+            for evaluation in [Evaluation::Scalar, Evaluation::Batched]
+            {
+                let id = BenchmarkId::new(format!("{method:?}/{evaluation:?}"), size);
+                group.bench_with_input(id, &size, |b, &size|
+                {
+                    b.iter_batched_ref(
+                        || new_grid(size),
+                        |grid| grid.fractalize(params(method, evaluation)).unwrap(),
+                        criterion::BatchSize::LargeInput,
+                    );
+                });
+            }
+        }
+    }
+
+    group.finish();
+}
+
+fn rgba8(c: &mut Criterion)
+{
+    bench_render(c, "splat_rgba8", |size| MyColorImage::new(size, size));
+}
+
+fn hist32(c: &mut Criterion)
+{
+    bench_render(c, "splat_hist32", |size| Histogram::new(size, size));
+}
+
+/// Exact versus polynomial sincos on the scalar path.
+fn trig_mode(c: &mut Criterion)
+{
+    let mut group = c.benchmark_group("trig_mode");
+    group.throughput(Throughput::Elements(POINTS as u64));
+
+    for trig in [TrigMode::Exact, TrigMode::Fast]
+    {
+        group.bench_function(format!("{trig:?}"), |b|
+        {
+            b.iter_batched_ref(
+                || Histogram::new(1024, 1024),
+                |grid| grid.fractalize(params(FractalMethod::Default, Evaluation::Scalar).with_trig(trig)).unwrap(),
+                criterion::BatchSize::LargeInput,
+            );
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, rgba8, hist32, trig_mode);
+criterion_main!(benches);
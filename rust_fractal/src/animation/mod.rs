@@ -0,0 +1,313 @@
+use std::path::Path;
+use std::time::Duration;
+
+use derive_setters::*;
+
+use crate::error::FractalError;
+use crate::export::write_via_part;
+use crate::fractal::{Fractalize, FractalizeParameters};
+use crate::my_grid::grid_32::MyColorImage;
+
+/// How a segment moves from one keyframe to the next.
+/// The easing of a keyframe applies to the segment that starts at it.
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Easing
+{
+    #[default]
+    Linear,
+    EaseIn,
+    EaseOut,
+    EaseInOut,
+    /// Keep this keyframe's values until the next keyframe is reached.
+    Hold,
+}
+
+impl Easing
+{
+    /// Maps linear progress `t` in [0, 1] through the easing curve.
+    pub fn apply(self, t: f32) -> f32
+    {
+        let t = t.clamp(0.0, 1.0);
+        match self
+        {
+            Easing::Linear => t,
+            Easing::EaseIn => t * t,
+            Easing::EaseOut => t * (2.0 - t),
+            Easing::EaseInOut => t * t * (3.0 - 2.0 * t),
+            Easing::Hold => 0.0,
+        }
+    }
+}
+
+#[derive(Setters, Clone, Copy, Debug, PartialEq)]
+#[setters(prefix = "with_")]
+pub struct Keyframe
+{
+    /// Time of the keyframe in seconds.
+    pub time: f32,
+    pub rot: f32,
+    pub theta_offset: f32,
+    pub easing: Easing,
+}
+
+impl Keyframe
+{
+    pub fn new(time: f32, rot: f32, theta_offset: f32) -> Self
+    {
+        Self { time, rot, theta_offset, easing: Easing::default() }
+    }
+}
+
+/// Describes an animation of `rot` and `theta_offset` over time.
+///
+/// Every frame is rendered with the same seed so the noise does not flicker
+/// between frames; only the animated parameters change.
+#[derive(Setters, Clone, Debug, PartialEq)]
+#[setters(prefix = "with_")]
+pub struct AnimationSpec
+{
+    /// Keyframes, sorted by time.
+    pub keyframes: Vec<Keyframe>,
+    pub frame_count: u32,
+    pub fps: f32,
+    pub width: u32,
+    pub height: u32,
+    /// Base parameters for every frame. `rot` and `theta_offset` are overwritten
+    /// by the keyframes, `seed` by the seed below.
+    pub params: FractalizeParameters,
+    pub seed: u64,
+}
+
+impl Default for AnimationSpec
+{
+    fn default() -> Self
+    {
+        Self
+        {
+            keyframes: Vec::new(),
+            frame_count: 60,
+            fps: 30.0,
+            width: 1024,
+            height: 1024,
+            params: FractalizeParameters::default(),
+            seed: 0,
+        }
+    }
+}
+
+impl AnimationSpec
+{
+    /// One frame every 100 seconds.
+    pub const MIN_FPS: f32 = 0.01;
+
+    /// A seamless loop sweeping `theta_offset` from -PI to PI.
+    /// The last keyframe lies one frame past the end, so the first frame is not repeated.
+    pub fn theta_offset_loop(frame_count: u32, fps: f32) -> Self
+    {
+        use std::f32::consts::PI;
+
+        let params = FractalizeParameters::default();
+        let end = frame_count as f32 / fps;
+
+        Self
+        {
+            keyframes: vec![
+                Keyframe::new(0.0, params.rot, -PI),
+                Keyframe::new(end, params.rot, PI),
+            ],
+            frame_count,
+            fps,
+            params,
+            ..Default::default()
+        }
+    }
+
+    /// Parameters used to render frame `frame` (0-based).
+    pub fn params_at(&self, frame: u32) -> FractalizeParameters
+    {
+        let t = frame as f32 / self.fps;
+        let (rot, theta_offset) = self.sample(t);
+
+        self.params
+        .with_rot(rot)
+        .with_theta_offset(theta_offset)
+        .with_seed(Some(self.seed))
+    }
+
+    /// Interpolated `(rot, theta_offset)` at time `t` seconds.
+    /// Before the first or after the last keyframe the values are held.
+    fn sample(&self, t: f32) -> (f32, f32)
+    {
+        let keys = &self.keyframes;
+        let (first, last) = match (keys.first(), keys.last())
+        {
+            (Some(first), Some(last)) => (first, last),
+            _ => return (self.params.rot, self.params.theta_offset),
+        };
+
+        if t <= first.time
+        {
+            return (first.rot, first.theta_offset);
+        }
+        if t >= last.time
+        {
+            return (last.rot, last.theta_offset);
+        }
+
+        let next = keys.partition_point(|k| k.time <= t);
+        let (a, b) = (&keys[next - 1], &keys[next]);
+
+        let span = b.time - a.time;
+        let s = if span > 0.0 { a.easing.apply((t - a.time) / span) } else { 1.0 };
+
+        (
+            a.rot + (b.rot - a.rot) * s,
+            a.theta_offset + (b.theta_offset - a.theta_offset) * s
+        )
+    }
+
+    /// File name of frame `frame` (0-based), numbered from 1: `frame_00001.png`.
+    pub fn frame_file_name(frame: u32) -> String
+    {
+        format!("frame_{:05}.png", frame + 1)
+    }
+
+    /// Checks the settings that are not covered by [`FractalizeParameters::validate`].
+    pub fn validate(&self) -> Result<(), FractalError>
+    {
+        if !(self.fps.is_finite() && self.fps > 0.0)
+        {
+            return Err(FractalError::InvalidAnimation("fps must be positive"));
+        }
+        // Slower rates make the frame delay overflow, and no format stores delays that long.
+        if self.fps < Self::MIN_FPS
+        {
+            return Err(FractalError::InvalidAnimation("fps must be at least 0.01"));
+        }
+        if self.frame_count == 0
+        {
+            return Err(FractalError::InvalidAnimation("there must be at least one frame"));
+        }
+        if self.keyframes.iter().any(|k| !(k.time.is_finite() && k.rot.is_finite() && k.theta_offset.is_finite()))
+        {
+            return Err(FractalError::InvalidAnimation("keyframes must be finite"));
+        }
+        if !self.keyframes.is_sorted_by(|a, b| a.time <= b.time)
+        {
+            return Err(FractalError::InvalidAnimation("keyframes must be sorted by time"));
+        }
+
+        Ok(())
+    }
+
+    /// Renders a single frame (0-based) with an opaque alpha channel.
+    pub fn render_frame(&self, frame: u32) -> Result<MyColorImage, FractalError>
+    {
+        let mut img = MyColorImage::new(self.width, self.height);
+        img.fractalize(self.params_at(frame))?;
+        img.pixels_mut().for_each(|p| p[3] = 0xff);
+
+        Ok(img)
+    }
+
+    /// Renders every frame into `dir` as numbered PNGs.
+    ///
+    /// Frames that already exist are skipped, so an interrupted export can be
+    /// resumed by calling this again. Each frame is written to a temporary file
+    /// first and renamed into place, so a partially written frame is never
+    /// mistaken for a finished one.
+    ///
+    /// Returns the number of frames that were rendered.
+    pub fn write_frames(&self, dir: impl AsRef<Path>) -> Result<u32, FractalError>
+    {
+        self.validate()?;
+
+        let dir = dir.as_ref();
+        std::fs::create_dir_all(dir)?;
+
+        let mut rendered = 0;
+        for frame in 0..self.frame_count
+        {
+            let path = dir.join(Self::frame_file_name(frame));
+            if path.exists()
+            {
+                continue;
+            }
+
+            let tmp = path.with_extension("png.part");
+            self.render_frame(frame)?.save_with_format(&tmp, image::ImageFormat::Png)?;
+            std::fs::rename(&tmp, &path)?;
+
+            rendered += 1;
+        }
+
+        Ok(rendered)
+    }
+
+    /// Renders the frames in order, one at a time.
+    pub fn frames(&self) -> impl Iterator<Item = Result<MyColorImage, FractalError>> + '_
+    {
+        (0..self.frame_count).map(|frame| self.render_frame(frame))
+    }
+
+    fn frame_delay(&self) -> Duration
+    {
+        Duration::from_secs_f32(1.0 / self.fps)
+    }
+
+    /// Writes the animation as an endlessly looping GIF.
+    ///
+    /// GIF frames are limited to a 256 color palette, so each frame is quantized.
+    /// Use [`AnimationSpec::write_apng`] to keep full color.
+    ///
+    /// Like [`AnimationSpec::write_frames`], the file only appears at `path` once it is complete.
+    pub fn write_gif(&self, path: impl AsRef<Path>) -> Result<(), FractalError>
+    {
+        self.validate()?;
+
+        use image::codecs::gif::{GifEncoder, Repeat};
+
+        write_via_part(path.as_ref(), |file|
+        {
+            // Speed 10 is the gif crate's recommended tradeoff between palette quality and time.
+            let mut encoder = GifEncoder::new_with_speed(file, 10);
+            encoder.set_repeat(Repeat::Infinite)?;
+
+            let delay = image::Delay::from_saturating_duration(self.frame_delay());
+            for frame in self.frames()
+            {
+                encoder.encode_frame(image::Frame::from_parts(frame?, 0, 0, delay))?;
+            }
+
+            Ok(())
+        })
+    }
+
+    /// Writes the animation as an endlessly looping, full color APNG.
+    ///
+    /// Like [`AnimationSpec::write_frames`], the file only appears at `path` once it is complete.
+    pub fn write_apng(&self, path: impl AsRef<Path>) -> Result<(), FractalError>
+    {
+        self.validate()?;
+
+        write_via_part(path.as_ref(), |file|
+        {
+            let mut encoder = png::Encoder::new(file, self.width, self.height);
+            encoder.set_color(png::ColorType::Rgba);
+            encoder.set_depth(png::BitDepth::Eight);
+            encoder.set_animated(self.frame_count, 0)?;
+
+            let delay_ms = self.frame_delay().as_millis().clamp(1, u16::MAX as u128) as u16;
+            encoder.set_frame_delay(delay_ms, 1000)?;
+
+            let mut writer = encoder.write_header()?;
+            for frame in self.frames()
+            {
+                writer.write_image_data(frame?.as_raw())?;
+            }
+            writer.finish()?;
+
+            Ok(())
+        })
+    }
+}
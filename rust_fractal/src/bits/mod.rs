@@ -0,0 +1,214 @@
+use rand::distr::{Distribution, Uniform};
+use rand::rngs::{StdRng, ThreadRng};
+use rand::{RngCore, SeedableRng};
+
+/// Where the branch decisions of the chaos game come from.
+///
+/// Each call hands out 64 decisions, one per bit, consumed from the least
+/// significant bit up. See [`crate::Fractalize::fractalize_with`].
+pub trait BitSource
+{
+    fn next_bits(&mut self) -> u64;
+}
+
+/// Lets a source be lent to a render and used again afterwards.
+impl<B: BitSource + ?Sized> BitSource for &mut B
+{
+    fn next_bits(&mut self) -> u64
+    {
+        (**self).next_bits()
+    }
+}
+
+/// The source [`crate::Fractalize::fractalize`] uses: [`StdRng`] seeded from
+/// `FractalizeParameters::seed`, or the thread rng when there is no seed.
+pub struct DefaultBits
+{
+    rng: DefaultRng,
+    distr: Uniform<u64>,
+}
+
+enum DefaultRng
+{
+    Seeded(Box<StdRng>),
+    Thread(ThreadRng),
+}
+
+impl DefaultBits
+{
+    pub fn new(seed: Option<u64>) -> Self
+    {
+        let rng = match seed
+        {
+            Some(seed) => DefaultRng::Seeded(Box::new(StdRng::seed_from_u64(seed))),
+            None => DefaultRng::Thread(rand::rng()),
+        };
+
+        Self
+        {
+            rng,
+            distr: Uniform::new(0, u64::MAX).unwrap(),
+        }
+    }
+}
+
+impl BitSource for DefaultBits
+{
+    fn next_bits(&mut self) -> u64
+    {
+        match &mut self.rng
+        {
+            DefaultRng::Seeded(rng) => self.distr.sample(rng.as_mut()),
+            DefaultRng::Thread(rng) => self.distr.sample(rng),
+        }
+    }
+}
+
+/// Any `rand` generator, taking its full 64 bit output.
+pub struct RngBits<R: RngCore>(pub R);
+
+impl<R: RngCore> BitSource for RngBits<R>
+{
+    fn next_bits(&mut self) -> u64
+    {
+        self.0.next_u64()
+    }
+}
+
+/// Seeded PCG, reproducible across platforms and `rand` releases unlike [`StdRng`].
+pub struct PcgBits(rand_pcg::Pcg64Mcg);
+
+impl PcgBits
+{
+    pub fn new(seed: u64) -> Self
+    {
+        Self(rand_pcg::Pcg64Mcg::seed_from_u64(seed))
+    }
+}
+
+impl BitSource for PcgBits
+{
+    fn next_bits(&mut self) -> u64
+    {
+        self.0.next_u64()
+    }
+}
+
+/// xorshift64*: a multiply and three shifts per 64 decisions.
+/// Statistically weaker than PCG, but the cheapest source here.
+pub struct XorshiftBits
+{
+    state: u64,
+}
+
+impl XorshiftBits
+{
+    pub fn new(seed: u64) -> Self
+    {
+        // The state must never be zero; mix the seed so that small seeds
+        // do not start out with mostly zero bits.
+        let state = seed.wrapping_mul(0x9e37_79b9_7f4a_7c15) | 1;
+        Self { state }
+    }
+}
+
+impl BitSource for XorshiftBits
+{
+    fn next_bits(&mut self) -> u64
+    {
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+        self.state.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+}
+
+/// Low-discrepancy decisions, for comparing how fast renders converge against
+/// the pseudo-random sources.
+///
+/// The decisions come from maximal-length linear feedback shift registers of
+/// growing order k, each run for its full period of 2^k - 1 decisions. Within a
+/// run every nonzero window of k consecutive decisions occurs exactly once, so
+/// the orbit's recent histories are spread evenly rather than at random: the
+/// completely uniformly distributed construction used for quasi-Monte Carlo
+/// sampling of Markov chains. Runs start at order 8 and grow by one up to 32,
+/// so any prefix of the sequence is stratified at about the depth it can afford.
+///
+/// On the attractors here the gain is small, a few percent less density error
+/// than [`PcgBits`] at a million points. The rotation branch is an isometry, so
+/// a point depends on older decisions as much as on recent ones.
+pub struct LowDiscrepancyBits
+{
+    state: u32,
+    order: u32,
+    /// Decisions left in the current run.
+    left: u64,
+}
+
+impl LowDiscrepancyBits
+{
+    const MIN_ORDER: u32 = 8;
+    const MAX_ORDER: u32 = 32;
+
+    /// Galois feedback taps of a primitive polynomial of each order, a term x^e at bit e - 1.
+    const TAPS: [u32; 33] = [
+        0, 0, 0x3, 0x6, 0xc, 0x14, 0x30, 0x60,
+        0xe1, 0x110, 0x240, 0x500, 0xe08, 0x1c80, 0x3802, 0x6000,
+        0xd008, 0x12000, 0x20400, 0x72000, 0x90000, 0x140000, 0x300000, 0x420000,
+        0xe10000, 0x1200000, 0x3880000, 0x7200000, 0x9000000, 0x14000000, 0x38000040, 0x48000000,
+        0xe0000200,
+    ];
+
+    /// `offset` picks where in the first run to start. Any value works.
+    pub fn new(offset: u32) -> Self
+    {
+        let order = Self::MIN_ORDER;
+        Self { state: Self::start_state(offset, order), order, left: Self::period(order) }
+    }
+
+    fn period(order: u32) -> u64
+    {
+        (1 << order) - 1
+    }
+
+    /// Zero is the one state a register never leaves.
+    fn start_state(state: u32, order: u32) -> u32
+    {
+        let state = (state as u64 & Self::period(order)) as u32;
+        if state == 0 { 1 } else { state }
+    }
+
+    fn next_bit(&mut self) -> u64
+    {
+        if self.left == 0
+        {
+            self.order = (self.order + 1).min(Self::MAX_ORDER);
+            self.state = Self::start_state(self.state, self.order);
+            self.left = Self::period(self.order);
+        }
+        self.left -= 1;
+
+        let out = self.state & 1;
+        self.state >>= 1;
+        if out != 0
+        {
+            self.state ^= Self::TAPS[self.order as usize];
+        }
+
+        out as u64
+    }
+}
+
+impl BitSource for LowDiscrepancyBits
+{
+    fn next_bits(&mut self) -> u64
+    {
+        let mut bits = 0;
+        for i in 0..64
+        {
+            bits |= self.next_bit() << i;
+        }
+
+        bits
+    }
+}
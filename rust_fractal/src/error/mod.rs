@@ -0,0 +1,109 @@
+use crate::fractal::{Symmetry, Viewport};
+use crate::my_grid::histogram::RawHistogramError;
+
+/// Everything that can go wrong while rendering or saving a fractal.
+#[derive(Debug)]
+pub enum FractalError
+{
+    /// A float parameter is NaN or infinite.
+    NonFinite
+    {
+        parameter: &'static str,
+        value: f32,
+    },
+    /// Fewer than 64 points are requested; branch decisions are drawn 64 at a time,
+    /// so nothing would be rendered.
+    TooFewPoints(u32),
+    /// The image or histogram to render into has no pixels.
+    ZeroSized
+    {
+        width: u32,
+        height: u32,
+    },
+    /// The viewport is empty, inverted or not finite.
+    InvalidViewport(Viewport),
+    /// A symmetry of order 0, which has no images at all, or above [`Symmetry::MAX_ORDER`].
+    InvalidSymmetry(Symmetry),
+    /// An animation setting is out of range.
+    InvalidAnimation(&'static str),
+    /// A framing setting is out of range, or the pre-pass found nothing to frame.
+    InvalidFraming(&'static str),
+    /// The render was stopped by its observer, see [`crate::Fractalize::fractalize_observed`].
+    Cancelled,
+    Image(image::ImageError),
+    Io(std::io::Error),
+    RawHistogram(RawHistogramError),
+}
+
+impl std::fmt::Display for FractalError
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result
+    {
+        match self
+        {
+            Self::NonFinite { parameter, value } => write!(f, "`{parameter}` must be finite, got {value}"),
+            Self::TooFewPoints(n) => write!(f, "at least 64 points are needed, got {n}"),
+            Self::ZeroSized { width, height } => write!(f, "cannot render into a {width}x{height} image"),
+            Self::InvalidViewport(v) => write!(f, "invalid viewport x: {}..{}, y: {}..{}", v.x_min, v.x_max, v.y_min, v.y_max),
+            Self::InvalidSymmetry(s) => write!(f, "symmetry order must be 1 to {}, got {}", Symmetry::MAX_ORDER, s.order),
+            Self::InvalidAnimation(reason) => write!(f, "invalid animation: {reason}"),
+            Self::InvalidFraming(reason) => write!(f, "cannot frame the attractor: {reason}"),
+            Self::Cancelled => write!(f, "the render was cancelled"),
+            Self::Image(e) => write!(f, "image error: {e}"),
+            Self::Io(e) => write!(f, "io error: {e}"),
+            Self::RawHistogram(e) => e.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for FractalError
+{
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)>
+    {
+        match self
+        {
+            Self::Image(e) => Some(e),
+            Self::Io(e) => Some(e),
+            Self::RawHistogram(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<image::ImageError> for FractalError
+{
+    fn from(e: image::ImageError) -> Self
+    {
+        Self::Image(e)
+    }
+}
+
+impl From<std::io::Error> for FractalError
+{
+    fn from(e: std::io::Error) -> Self
+    {
+        Self::Io(e)
+    }
+}
+
+impl From<png::EncodingError> for FractalError
+{
+    fn from(e: png::EncodingError) -> Self
+    {
+        use image::error::{EncodingError, ImageFormatHint};
+
+        match e
+        {
+            png::EncodingError::IoError(e) => Self::Io(e),
+            e => Self::Image(image::ImageError::Encoding(EncodingError::new(ImageFormatHint::Exact(image::ImageFormat::Png), e))),
+        }
+    }
+}
+
+impl From<RawHistogramError> for FractalError
+{
+    fn from(e: RawHistogramError) -> Self
+    {
+        Self::RawHistogram(e)
+    }
+}
@@ -0,0 +1,394 @@
+use std::f64::consts::PI;
+
+use derive_setters::*;
+use rand::{Rng, SeedableRng};
+
+use crate::bits::{BitSource, DefaultBits};
+use crate::error::FractalError;
+use crate::fractal::{FractalMethod, Fractalize, FractalizeParameters};
+use crate::my_grid::histogram::Histogram;
+
+/// Cheap measures of how interesting a render is, taken from a small preview.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Metrics
+{
+    /// Fraction of pixels hit at least once.
+    pub coverage: f64,
+    /// Shannon entropy of the hit density, divided by that of a uniform density,
+    /// so in [0, 1]. Low for a few bright spots, high for evenly spread hits.
+    pub entropy: f64,
+    /// Estimate of the fractal dimension, in [0, 2], see [`information_dimension`].
+    pub dimension: f64,
+    /// Estimate of the largest Lyapunov exponent of the orbit, in nats per step.
+    /// Strongly negative when the orbit collapses onto a few points.
+    pub lyapunov: f64,
+}
+
+impl Metrics
+{
+    /// Steps of the orbit followed for the Lyapunov estimate.
+    pub const LYAPUNOV_STEPS: u32 = 1 << 16;
+
+    /// Measures `preview`, a render of `p`.
+    pub fn of(preview: &Histogram, p: &FractalizeParameters) -> Self
+    {
+        Self::of_with_steps(preview, p, Self::LYAPUNOV_STEPS)
+    }
+
+    fn of_with_steps(preview: &Histogram, p: &FractalizeParameters, lyapunov_steps: u32) -> Self
+    {
+        Self
+        {
+            coverage: coverage(preview),
+            entropy: entropy(preview),
+            dimension: information_dimension(preview),
+            lyapunov: lyapunov(p, lyapunov_steps),
+        }
+    }
+
+    /// Renders a `size`² preview of `p` and measures it.
+    pub fn measure(p: &FractalizeParameters, size: u32) -> Result<(Self, Histogram), FractalError>
+    {
+        let mut preview = Histogram::new(size, size);
+        preview.fractalize(*p)?;
+
+        Ok((Self::of(&preview, p), preview))
+    }
+
+    /// The default ranking used by [`RandomSearch`], higher is better, in [0, 1].
+    ///
+    /// Favours evenly spread, finely structured images: high entropy, a
+    /// dimension well between a curve's and a filled area's, and a fair part
+    /// of the image covered. Previews of uniform haze measure close to 2,
+    /// filaments and folds pull the dimension down. Orbits that collapse score zero.
+    pub fn score(&self) -> f64
+    {
+        if self.lyapunov.is_nan() || self.lyapunov < -1.0 || self.coverage == 0.0
+        {
+            return 0.0;
+        }
+
+        let structure = (1.0 - ((self.dimension - 1.6) / 0.5).powi(2)).max(0.0);
+        let spread = (4.0 * self.coverage * (1.0 - self.coverage)).sqrt();
+
+        self.entropy * structure * spread
+    }
+}
+
+/// Fraction of pixels with at least one hit.
+pub fn coverage(h: &Histogram) -> f64
+{
+    let hit = h.counts().iter().filter(|&&n| n > 0).count();
+    hit as f64 / h.counts().len() as f64
+}
+
+/// Entropy of the normalised hit density over that of a uniform density, in [0, 1].
+pub fn entropy(h: &Histogram) -> f64
+{
+    let total: f64 = h.counts().iter().map(|&n| n as f64).sum();
+    if total == 0.0 || h.counts().len() < 2
+    {
+        return 0.0;
+    }
+
+    let bits: f64 = h.counts().iter()
+        .filter(|&&n| n > 0)
+        .map(|&n| { let q = n as f64 / total; -q * q.log2() })
+        .sum();
+
+    bits / (h.counts().len() as f64).log2()
+}
+
+/// Information dimension: the slope of the entropy of the hit density,
+/// binned into boxes of 1, 2, 4, ... pixels up to an eighth of the shorter side,
+/// against log(1 / box size).
+///
+/// Unlike counting occupied boxes this weighs boxes by their hits, so a faint
+/// haze of stray points does not read as a filled area.
+pub fn information_dimension(h: &Histogram) -> f64
+{
+    let (width, height) = (h.width() as usize, h.height() as usize);
+    let total: f64 = h.counts().iter().map(|&n| n as f64).sum();
+    if total == 0.0
+    {
+        return 0.0;
+    }
+
+    let mut points = Vec::new();
+    let mut size = 1;
+    while size * 8 <= width.min(height)
+    {
+        let cols = width.div_ceil(size);
+        let mut boxes = vec![0_u64; cols * height.div_ceil(size)];
+        for (i, &n) in h.counts().iter().enumerate()
+        {
+            boxes[(i / width / size) * cols + (i % width) / size] += n as u64;
+        }
+
+        let entropy: f64 = boxes.iter()
+            .filter(|&&n| n > 0)
+            .map(|&n| { let q = n as f64 / total; -q * q.ln() })
+            .sum();
+        points.push(((1.0 / size as f64).ln(), entropy));
+        size *= 2;
+    }
+
+    if points.len() < 2
+    {
+        return 0.0;
+    }
+
+    // Least squares slope.
+    let n = points.len() as f64;
+    let (sx, sy) = points.iter().fold((0.0, 0.0), |(sx, sy), (x, y)| (sx + x, sy + y));
+    let (mx, my) = (sx / n, sy / n);
+    let (sxy, sxx) = points.iter()
+        .fold((0.0, 0.0), |(sxy, sxx), (x, y)| (sxy + (x - mx) * (y - my), sxx + (x - mx) * (x - mx)));
+
+    (sxy / sxx).clamp(0.0, 2.0)
+}
+
+/// Estimates the largest Lyapunov exponent of the orbit of `p` over `steps` steps.
+///
+/// A tangent vector is carried along the orbit through the Jacobian of every
+/// transform taken and renormalised each step; the exponent is the mean log of
+/// its growth. The rotation preserves lengths, so only the polar branch
+/// contributes. Runs in f64 with the exact trig, whatever `p` asks for.
+pub fn lyapunov(p: &FractalizeParameters, steps: u32) -> f64
+{
+    let mut bits = DefaultBits::new(p.seed);
+
+    let (rot_sin, rot_cos) = (p.rot as f64).sin_cos();
+    let theta_offset = p.theta_offset as f64;
+    // theta = y * theta_scale + theta_shift, as in the renderer.
+    let (theta_scale, theta_shift) = match p.method
+    {
+        FractalMethod::Default => (PI, theta_offset),
+        FractalMethod::MultiplyTheta => (PI * theta_offset, 0.0),
+    };
+
+    let (mut x, mut y) = (p.init_x_y.0 as f64, p.init_x_y.1 as f64);
+    let (mut tx, mut ty) = (1.0, 0.0);
+    let mut sum = 0.0;
+
+    let mut word = 0;
+    for i in 0..steps
+    {
+        if i % 64 == 0
+        {
+            word = bits.next_bits();
+        }
+
+        // The orbit continues with the rotation when the bit is set.
+        if word & (1 << (i % 64)) != 0
+        {
+            (x, y) = (x * rot_cos + y * rot_sin, y * rot_cos - x * rot_sin);
+            (tx, ty) = (tx * rot_cos + ty * rot_sin, ty * rot_cos - tx * rot_sin);
+        }
+        else
+        {
+            let rad = x * 0.5 + 0.5;
+            let (sin, cos) = (y * theta_scale + theta_shift).sin_cos();
+            (x, y) = (rad * cos, rad * sin);
+            (tx, ty) = (
+                0.5 * cos * tx - rad * sin * theta_scale * ty,
+                0.5 * sin * tx + rad * cos * theta_scale * ty,
+            );
+        }
+
+        let len = (tx * tx + ty * ty).sqrt();
+        if len == 0.0 || !len.is_finite()
+        {
+            // The tangent collapsed exactly, e.g. at rad == 0.
+            return f64::NEG_INFINITY;
+        }
+        sum += len.ln();
+        (tx, ty) = (tx / len, ty / len);
+    }
+
+    sum / steps as f64
+}
+
+/// One parameter set found by [`RandomSearch`].
+#[derive(Clone, Debug)]
+pub struct Candidate
+{
+    pub params: FractalizeParameters,
+    pub metrics: Metrics,
+    pub score: f64,
+    /// The preview the metrics were measured on, handy as a thumbnail.
+    pub preview: Histogram,
+}
+
+/// Tries random `rot` / `theta_offset` pairs in [-PI, PI]² and keeps the best.
+///
+/// Everything else comes from `base`. Each candidate is rendered as a
+/// `preview_size`² preview of `preview_points` points, seeded with `seed`,
+/// so a search is reproducible.
+#[derive(Setters, Clone, Copy, Debug, PartialEq)]
+#[setters(prefix = "with_")]
+pub struct RandomSearch
+{
+    pub base: FractalizeParameters,
+    pub candidates: u32,
+    pub top_k: usize,
+    pub preview_size: u32,
+    pub preview_points: u32,
+    pub seed: u64,
+}
+
+impl Default for RandomSearch
+{
+    fn default() -> Self
+    {
+        Self
+        {
+            base: FractalizeParameters::default(),
+            candidates: 64,
+            top_k: 8,
+            preview_size: 128,
+            preview_points: 200_000,
+            seed: 0,
+        }
+    }
+}
+
+impl RandomSearch
+{
+    /// The `top_k` best candidates by [`Metrics::score`], best first.
+    pub fn run(&self) -> Result<Vec<Candidate>, FractalError>
+    {
+        self.run_with(Metrics::score)
+    }
+
+    /// Like [`RandomSearch::run`], ranking by `score` instead.
+    pub fn run_with(&self, score: impl Fn(&Metrics) -> f64) -> Result<Vec<Candidate>, FractalError>
+    {
+        let mut rng = rand_pcg::Pcg64Mcg::seed_from_u64(self.seed);
+        let mut best: Vec<Candidate> = Vec::with_capacity(self.top_k + 1);
+
+        for _ in 0..self.candidates
+        {
+            let params = self.base
+                .with_rot(rng.random_range(-std::f32::consts::PI..=std::f32::consts::PI))
+                .with_theta_offset(rng.random_range(-std::f32::consts::PI..=std::f32::consts::PI))
+                .with_max_points(self.preview_points)
+                .with_seed(Some(self.seed));
+
+            let (metrics, preview) = Metrics::measure(&params, self.preview_size)?;
+            let candidate = Candidate { params, metrics, score: score(&metrics), preview };
+
+            let at = best.partition_point(|c| c.score >= candidate.score);
+            if at < self.top_k
+            {
+                best.insert(at, candidate);
+                best.truncate(self.top_k);
+            }
+        }
+
+        Ok(best)
+    }
+}
+
+/// The number a [`ParameterMap`] shows for each pair.
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MapMetric
+{
+    #[default]
+    Coverage,
+    Entropy,
+    Dimension,
+    /// Needs no render, only the orbit.
+    Lyapunov,
+    /// [`Metrics::score`].
+    Score,
+}
+
+/// Samples a metric over a grid of `rot` / `theta_offset` pairs covering
+/// [-PI, PI]², `rot` across the columns and `theta_offset` down the rows.
+///
+/// Every cell is a tiny render of the pair at its center, with everything
+/// else from `base` and seeded with `seed`. Rows are computed one at a time
+/// with [`ParameterMap::row`] so that a map can be filled in progressively.
+#[derive(Setters, Clone, Copy, Debug, PartialEq)]
+#[setters(prefix = "with_")]
+pub struct ParameterMap
+{
+    pub base: FractalizeParameters,
+    /// Cells along each side.
+    pub size: u32,
+    pub preview_size: u32,
+    pub preview_points: u32,
+    pub metric: MapMetric,
+    pub seed: u64,
+}
+
+impl Default for ParameterMap
+{
+    fn default() -> Self
+    {
+        Self
+        {
+            base: FractalizeParameters::default(),
+            size: 128,
+            preview_size: 32,
+            preview_points: 16_384,
+            metric: MapMetric::default(),
+            seed: 0,
+        }
+    }
+}
+
+impl ParameterMap
+{
+    /// Orbit steps for the Lyapunov estimate of a cell; far fewer than
+    /// [`Metrics::LYAPUNOV_STEPS`], since a map has thousands of cells.
+    const LYAPUNOV_STEPS: u32 = 4096;
+
+    /// The parameters at `(u, v)` in [0, 1]², `u` across and `v` down the map.
+    pub fn params_at(&self, u: f32, v: f32) -> FractalizeParameters
+    {
+        use std::f32::consts::PI;
+
+        self.base
+            .with_rot(-PI + u * 2.0 * PI)
+            .with_theta_offset(-PI + v * 2.0 * PI)
+            .with_max_points(self.preview_points)
+            .with_seed(Some(self.seed))
+    }
+
+    /// The parameters at the center of cell (`col`, `row`).
+    pub fn cell(&self, col: u32, row: u32) -> FractalizeParameters
+    {
+        let n = self.size as f32;
+        self.params_at((col as f32 + 0.5) / n, (row as f32 + 0.5) / n)
+    }
+
+    /// The metric of a single cell.
+    pub fn value(&self, col: u32, row: u32) -> Result<f64, FractalError>
+    {
+        let p = self.cell(col, row);
+        if self.metric == MapMetric::Lyapunov
+        {
+            return Ok(lyapunov(&p, Self::LYAPUNOV_STEPS));
+        }
+
+        let mut preview = Histogram::new(self.preview_size, self.preview_size);
+        preview.fractalize(p)?;
+
+        Ok(match self.metric
+        {
+            MapMetric::Coverage => coverage(&preview),
+            MapMetric::Entropy => entropy(&preview),
+            MapMetric::Dimension => information_dimension(&preview),
+            MapMetric::Lyapunov => unreachable!(),
+            MapMetric::Score => Metrics::of_with_steps(&preview, &p, Self::LYAPUNOV_STEPS).score(),
+        })
+    }
+
+    /// The metric of every cell of `row`, left to right.
+    pub fn row(&self, row: u32) -> Result<Vec<f64>, FractalError>
+    {
+        (0..self.size).map(|col| self.value(col, row)).collect()
+    }
+}
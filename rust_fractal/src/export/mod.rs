@@ -0,0 +1,160 @@
+use std::ffi::OsString;
+use std::fs::File;
+use std::io::{BufWriter, Seek, Write};
+use std::path::{Path, PathBuf};
+
+use derive_setters::*;
+use image::buffer::ConvertBuffer;
+
+use crate::error::FractalError;
+use crate::fractal::{FractalMethod, FractalizeParameters};
+use crate::my_grid::histogram::Histogram;
+use crate::tonemap::Tonemap;
+
+/// File formats a render can be saved in.
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ExportFormat
+{
+    #[default]
+    Png,
+    /// Lossy and without alpha, but small.
+    Jpeg,
+    Tiff,
+    /// RGB at 16 bits per channel, tonemapped again at full depth.
+    Png16,
+}
+
+impl ExportFormat
+{
+    pub const ALL: [Self; 4] = [Self::Png, Self::Jpeg, Self::Tiff, Self::Png16];
+
+    pub fn extension(self) -> &'static str
+    {
+        match self
+        {
+            Self::Png | Self::Png16 => "png",
+            Self::Jpeg => "jpg",
+            Self::Tiff => "tiff",
+        }
+    }
+
+    pub fn name(self) -> &'static str
+    {
+        match self
+        {
+            Self::Png => "PNG",
+            Self::Jpeg => "JPEG",
+            Self::Tiff => "TIFF",
+            Self::Png16 => "16-bit PNG",
+        }
+    }
+}
+
+/// Tonemaps a [`Histogram`] and encodes it as an image file.
+#[derive(Setters, Clone, Copy, Debug, PartialEq)]
+#[setters(prefix = "with_")]
+pub struct Export
+{
+    pub format: ExportFormat,
+    /// 1 to 100, only used by [`ExportFormat::Jpeg`].
+    pub jpeg_quality: u8,
+}
+
+impl Default for Export
+{
+    fn default() -> Self
+    {
+        Self { format: ExportFormat::default(), jpeg_quality: 90 }
+    }
+}
+
+impl Export
+{
+    pub fn write(&self, histogram: &Histogram, tonemap: &Tonemap, mut w: impl Write + Seek) -> Result<(), FractalError>
+    {
+        match self.format
+        {
+            ExportFormat::Png => tonemap.apply(histogram).write_to(&mut w, image::ImageFormat::Png)?,
+            ExportFormat::Tiff => tonemap.apply(histogram).write_to(&mut w, image::ImageFormat::Tiff)?,
+            ExportFormat::Png16 => tonemap.apply_16(histogram).write_to(&mut w, image::ImageFormat::Png)?,
+            ExportFormat::Jpeg =>
+            {
+                use image::codecs::jpeg::JpegEncoder;
+
+                let rgb: image::RgbImage = tonemap.apply(histogram).convert();
+                JpegEncoder::new_with_quality(&mut w, self.jpeg_quality.clamp(1, 100)).encode_image(&rgb)?;
+            },
+        }
+        w.flush()?;
+
+        Ok(())
+    }
+
+    /// Writes to a temporary file next to `path` and renames it into place,
+    /// so a failed save never leaves a truncated image behind.
+    pub fn save(&self, histogram: &Histogram, tonemap: &Tonemap, path: impl AsRef<Path>) -> Result<(), FractalError>
+    {
+        write_via_part(path.as_ref(), |w| self.write(histogram, tonemap, w))
+    }
+}
+
+/// Runs `write` on `path` with `.part` appended, and renames the file into place
+/// once it is complete. On failure the partial file is removed, so nothing that
+/// looks finished is left behind.
+pub(crate) fn write_via_part(
+    path: &Path,
+    write: impl FnOnce(&mut BufWriter<File>) -> Result<(), FractalError>,
+) -> Result<(), FractalError>
+{
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".part");
+
+    let result = File::create(&tmp)
+        .map_err(FractalError::from)
+        .and_then(|file|
+        {
+            let mut w = BufWriter::new(file);
+            write(&mut w)?;
+            Ok(w.flush()?)
+        })
+        .and_then(|()| Ok(std::fs::rename(&tmp, path)?));
+    if result.is_err()
+    {
+        let _ = std::fs::remove_file(&tmp);
+    }
+
+    result
+}
+
+/// A file name stem describing `p`, like `fractal_rot+0.5000_theta-1.2500_sym6m`.
+pub fn parameter_stem(p: &FractalizeParameters) -> String
+{
+    let mut stem = format!("fractal_rot{:+.4}_theta{:+.4}", p.rot, p.theta_offset);
+    if p.method == FractalMethod::MultiplyTheta
+    {
+        stem.push_str("_mul");
+    }
+    if p.symmetry.order > 1 || p.symmetry.mirror
+    {
+        stem.push_str(&format!("_sym{}{}", p.symmetry.order, if p.symmetry.mirror { "m" } else { "" }));
+    }
+    stem
+}
+
+/// `stem.extension` if no such file exists, otherwise the first free one of
+/// `stem_2.extension`, `stem_3.extension`, ...
+pub fn next_free_path(stem: impl AsRef<Path>, extension: &str) -> PathBuf
+{
+    let stem = stem.as_ref().as_os_str();
+    let with_suffix = |suffix: &str|
+    {
+        let mut name = OsString::from(stem);
+        name.push(format!("{suffix}.{extension}"));
+        PathBuf::from(name)
+    };
+
+    std::iter::once(with_suffix(""))
+        .chain((2..).map(|n| with_suffix(&format!("_{n}"))))
+        .find(|path| !path.exists())
+        .unwrap()
+}
@@ -1,9 +1,37 @@
+use std::ops::ControlFlow;
+
 use derive_setters::*;
 use derive_getters::*;
 
+use crate::bits::{BitSource, DefaultBits};
+use crate::error::FractalError;
+
 pub trait Fractalize
 {
-    fn fractalize(&mut self, p: FractalizeParameters) -> ();
+    /// Renders `p` into `self`. The parameters are validated first,
+    /// and nothing is drawn if they are rejected.
+    fn fractalize(&mut self, p: FractalizeParameters) -> Result<(), FractalError>
+    {
+        self.fractalize_with(p, DefaultBits::new(p.seed))
+    }
+
+    /// Like [`Fractalize::fractalize`], but takes the branch decisions from `bits`.
+    /// `p.seed` is ignored; seed the source instead.
+    fn fractalize_with<B: BitSource>(&mut self, p: FractalizeParameters, bits: B) -> Result<(), FractalError>
+    {
+        self.fractalize_observed(p, bits, |_, _| ControlFlow::Continue(()))
+    }
+
+    /// Like [`Fractalize::fractalize_with`], but calls `observe` with the grid
+    /// and the number of points drawn so far after every few hundred thousand
+    /// points, and once more at the end. Returning [`ControlFlow::Break`] stops
+    /// the render with [`FractalError::Cancelled`], keeping what was drawn.
+    fn fractalize_observed<B: BitSource>(
+        &mut self,
+        p: FractalizeParameters,
+        bits: B,
+        observe: impl FnMut(&Self, u64) -> ControlFlow<()>,
+    ) -> Result<(), FractalError>;
 }
 
 #[derive(Setters, Getters, Clone, Copy, Debug, PartialEq)]
@@ -17,6 +45,17 @@ pub struct FractalizeParameters
     pub theta_offset: f32,
     pub method: FractalMethod,
     pub max_points: u32,
+    /// Seed for the branch decisions. `None` draws from the thread rng,
+    /// so repeated renders differ slightly in their noise.
+    pub seed: Option<u64>,
+    pub evaluation: Evaluation,
+    /// Sine and cosine used by the scalar polar branch. The batched path
+    /// always uses the fast polynomial, since that is what lets it vectorise.
+    pub trig: TrigMode,
+    pub precision: Precision,
+    /// Region of the plane mapped onto the image.
+    pub viewport: Viewport,
+    pub symmetry: Symmetry,
 }
 
 #[derive(Default, Clone, Copy, Debug, PartialEq, Eq)]
@@ -27,8 +66,132 @@ pub enum FractalMethod
     MultiplyTheta,
 }
 
+/// How the orbit is advanced.
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Evaluation
+{
+    /// One orbit, one point at a time, with the standard library's `sin`/`cos`.
+    #[default]
+    Scalar,
+    /// Several independent orbits in lockstep, with a vectorised polynomial sincos.
+    /// Statistically equivalent to `Scalar`, but not pixel identical.
+    Batched,
+}
+
+/// How `sin` and `cos` are computed in the polar branch.
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TrigMode
+{
+    /// The standard library's `f32::sin` and `f32::cos`. Ignored by [`Evaluation::Batched`].
+    #[default]
+    Exact,
+    /// A polynomial approximation, see [`crate::fast_sincos`] for its error bound.
+    /// Orbits drift apart from `Exact` ones, but the rendered density matches.
+    Fast,
+}
+
+/// Float type the orbit is iterated in.
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Precision
+{
+    #[default]
+    F32,
+    /// Needed for deep zooms into small viewports, where f32 runs out of
+    /// precision and the structure turns to mush. Always uses the scalar path.
+    F64,
+}
+
+/// The rectangle of the plane that is drawn, `x` across the columns and `y` down the rows.
+/// The attractor lives in [-1, 1]², which is the default.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Viewport
+{
+    pub x_min: f64,
+    pub x_max: f64,
+    pub y_min: f64,
+    pub y_max: f64,
+}
+
+impl Default for Viewport
+{
+    fn default() -> Self
+    {
+        Self { x_min: -1.0, x_max: 1.0, y_min: -1.0, y_max: 1.0 }
+    }
+}
+
+impl Viewport
+{
+    /// A viewport centered on `(x, y)`, extending `half_extent` in every direction.
+    pub fn centered(x: f64, y: f64, half_extent: f64) -> Self
+    {
+        Self
+        {
+            x_min: x - half_extent,
+            x_max: x + half_extent,
+            y_min: y - half_extent,
+            y_max: y + half_extent,
+        }
+    }
+
+    pub fn width(&self) -> f64
+    {
+        self.x_max - self.x_min
+    }
+
+    pub fn height(&self) -> f64
+    {
+        self.y_max - self.y_min
+    }
+
+    pub fn center(&self) -> (f64, f64)
+    {
+        ((self.x_min + self.x_max) * 0.5, (self.y_min + self.y_max) * 0.5)
+    }
+}
+
+/// Extra copies of every sample, rotated and optionally mirrored about the origin.
+/// Each orbit sample is splatted [`Symmetry::images`] times, so renders take
+/// longer but `max_points` keeps counting orbit samples.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Symmetry
+{
+    /// N-fold rotational symmetry, 1 for none.
+    pub order: u32,
+    /// Also mirror across the x axis, giving the dihedral group of `order`.
+    pub mirror: bool,
+}
+
+impl Default for Symmetry
+{
+    fn default() -> Self
+    {
+        Self::NONE
+    }
+}
+
+impl Symmetry
+{
+    pub const NONE: Self = Self { order: 1, mirror: false };
+    /// Highest order [`FractalizeParameters::validate`] accepts. Every sample is
+    /// splatted once per image, so far fewer are useful anyway.
+    pub const MAX_ORDER: u32 = 64;
+
+    pub fn new(order: u32, mirror: bool) -> Self
+    {
+        Self { order, mirror }
+    }
+
+    /// How many times each sample is splatted, itself included.
+    pub fn images(&self) -> u32
+    {
+        self.order * if self.mirror { 2 } else { 1 }
+    }
+}
+
 impl Default for FractalizeParameters
 {
+    #[allow(clippy::excessive_precision)]
     fn default() -> Self {
         Self 
         { 
@@ -36,7 +199,49 @@ impl Default for FractalizeParameters
             rot: 1.724643921305295,
             theta_offset: 3.0466792337230033,
             method: FractalMethod::default(),
-            max_points: 1_000_000
+            max_points: 1_000_000,
+            seed: None,
+            evaluation: Evaluation::default(),
+            trig: TrigMode::default(),
+            precision: Precision::default(),
+            viewport: Viewport::default(),
+            symmetry: Symmetry::default(),
+        }
+    }
+}
+
+impl FractalizeParameters
+{
+    /// Checks for parameters that would silently render nothing.
+    pub fn validate(&self) -> Result<(), FractalError>
+    {
+        let floats = [
+            ("init_x_y.0", self.init_x_y.0),
+            ("init_x_y.1", self.init_x_y.1),
+            ("rot", self.rot),
+            ("theta_offset", self.theta_offset),
+        ];
+        if let Some(&(parameter, value)) = floats.iter().find(|(_, v)| !v.is_finite())
+        {
+            return Err(FractalError::NonFinite { parameter, value });
+        }
+
+        if self.max_points < 64
+        {
+            return Err(FractalError::TooFewPoints(self.max_points));
+        }
+
+        let Viewport { x_min, x_max, y_min, y_max } = self.viewport;
+        if ![x_min, x_max, y_min, y_max].iter().all(|v| v.is_finite()) || x_min >= x_max || y_min >= y_max
+        {
+            return Err(FractalError::InvalidViewport(self.viewport));
+        }
+
+        if !(1..=Symmetry::MAX_ORDER).contains(&self.symmetry.order)
+        {
+            return Err(FractalError::InvalidSymmetry(self.symmetry));
         }
+
+        Ok(())
     }
 }
\ No newline at end of file
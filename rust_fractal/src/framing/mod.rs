@@ -0,0 +1,148 @@
+use derive_setters::*;
+
+use crate::error::FractalError;
+use crate::fractal::{Fractalize, FractalizeParameters, Viewport};
+use crate::my_grid::histogram::Histogram;
+
+/// Fits a viewport to the attractor, from a short pre-pass.
+///
+/// The pre-pass renders `samples` points into a coarse histogram of a region
+/// well beyond [-1, 1]², then drops the outermost `trim` of the hits on each
+/// side, so that the transient from `init_x_y` and rare outliers do not widen
+/// the frame.
+#[derive(Setters, Clone, Copy, Debug, PartialEq)]
+#[setters(prefix = "with_")]
+pub struct Framing
+{
+    pub samples: u32,
+    /// Fraction of the hits ignored on each side, in [0, 0.5).
+    pub trim: f64,
+    /// Padding on every side, as a fraction of the fitted width and height.
+    pub margin: f64,
+    /// Width over height of the image; the shorter side of the fit is widened to match.
+    pub aspect: f64,
+}
+
+impl Default for Framing
+{
+    fn default() -> Self
+    {
+        Self
+        {
+            samples: 1 << 18,
+            trim: 0.001,
+            margin: 0.05,
+            aspect: 1.0,
+        }
+    }
+}
+
+impl Framing
+{
+    /// Width and height of the pre-pass histogram. The fit is accurate to
+    /// about one of its cells, which the margin more than covers.
+    const RESOLUTION: u32 = 1024;
+
+    pub fn validate(&self) -> Result<(), FractalError>
+    {
+        if !(0.0..0.5).contains(&self.trim)
+        {
+            return Err(FractalError::InvalidFraming("trim must be in [0, 0.5)"));
+        }
+        if !(self.margin >= 0.0 && self.margin.is_finite())
+        {
+            return Err(FractalError::InvalidFraming("margin must be finite and not negative"));
+        }
+        if !(self.aspect > 0.0 && self.aspect.is_finite())
+        {
+            return Err(FractalError::InvalidFraming("aspect must be finite and positive"));
+        }
+
+        Ok(())
+    }
+
+    /// The viewport that frames the attractor of `p`. Everything but the
+    /// viewport and the number of points is taken from `p`, seed included.
+    pub fn fit(&self, p: &FractalizeParameters) -> Result<Viewport, FractalError>
+    {
+        self.validate()?;
+
+        // The attractor stays within the unit disk, but the orbit may start
+        // outside of it.
+        let (x0, y0) = p.init_x_y;
+        let reach = 2.0_f64.max(2.0 * x0.abs().max(y0.abs()) as f64);
+        let search = Viewport::centered(0.0, 0.0, reach);
+
+        let n = Self::RESOLUTION;
+        let mut histogram = Histogram::new(n, n);
+        histogram.fractalize(p.with_viewport(search).with_max_points(self.samples))?;
+
+        let mut cols = vec![0_u64; n as usize];
+        let mut rows = vec![0_u64; n as usize];
+        for (i, &count) in histogram.counts().iter().enumerate()
+        {
+            cols[i % n as usize] += count as u64;
+            rows[i / n as usize] += count as u64;
+        }
+
+        let total: u64 = cols.iter().sum();
+        if total == 0
+        {
+            return Err(FractalError::InvalidFraming("no samples landed near the attractor"));
+        }
+
+        let cell = search.width() / n as f64;
+        let (c_lo, c_hi) = self.trimmed_range(&cols, total);
+        let (r_lo, r_hi) = self.trimmed_range(&rows, total);
+
+        let mut x_min = search.x_min + c_lo as f64 * cell;
+        let mut x_max = search.x_min + (c_hi + 1) as f64 * cell;
+        let mut y_min = search.y_min + r_lo as f64 * cell;
+        let mut y_max = search.y_min + (r_hi + 1) as f64 * cell;
+
+        let (width, height) = (x_max - x_min, y_max - y_min);
+        x_min -= width * self.margin;
+        x_max += width * self.margin;
+        y_min -= height * self.margin;
+        y_max += height * self.margin;
+
+        let (cx, cy) = ((x_min + x_max) * 0.5, (y_min + y_max) * 0.5);
+        let (width, height) = (x_max - x_min, y_max - y_min);
+        if width < height * self.aspect
+        {
+            let half = height * self.aspect * 0.5;
+            (x_min, x_max) = (cx - half, cx + half);
+        }
+        else
+        {
+            let half = width / self.aspect * 0.5;
+            (y_min, y_max) = (cy - half, cy + half);
+        }
+
+        Ok(Viewport { x_min, x_max, y_min, y_max })
+    }
+
+    /// First and last index left after dropping `trim` of `total` from each end.
+    fn trimmed_range(&self, counts: &[u64], total: u64) -> (usize, usize)
+    {
+        // `trim` < 0.5, so some hits are always left in between.
+        let cut = (total as f64 * self.trim) as u64;
+        let first_past_cut = |counts: &mut dyn Iterator<Item = &u64>|
+        {
+            let mut seen = 0;
+            for (i, &n) in counts.enumerate()
+            {
+                seen += n;
+                if seen > cut
+                {
+                    return i;
+                }
+            }
+            unreachable!("fewer than `total` hits")
+        };
+
+        let lo = first_past_cut(&mut counts.iter());
+        let hi = counts.len() - 1 - first_past_cut(&mut counts.iter().rev());
+        (lo, hi)
+    }
+}
@@ -1,9 +1,26 @@
+mod animation;
+mod bits;
+mod error;
+mod explore;
+mod export;
 mod fractal;
+mod framing;
 mod my_grid;
+mod tonemap;
 
 // for full fractal code, see https://github.com/etscheelk/RustFractal.
 // also contains gpu experiementation.
 // This is a pared down version of the fractal code.
 
-pub use crate::fractal::{Fractalize, FractalizeParameters, FractalMethod};
-pub use crate::my_grid::grid_32::MyColorImage;
\ No newline at end of file
+pub use crate::animation::{AnimationSpec, Easing, Keyframe};
+pub use crate::bits::{BitSource, DefaultBits, LowDiscrepancyBits, PcgBits, RngBits, XorshiftBits};
+pub use crate::error::FractalError;
+pub use crate::explore::{coverage, entropy, information_dimension, lyapunov, Candidate, MapMetric, Metrics, ParameterMap, RandomSearch};
+pub use crate::export::{next_free_path, parameter_stem, Export, ExportFormat};
+pub use crate::framing::Framing;
+pub use crate::fractal::{Evaluation, Fractalize, FractalizeParameters, FractalMethod, Precision, Symmetry, TrigMode, Viewport};
+pub use crate::my_grid::Accumulate;
+pub use crate::my_grid::grid_32::MyColorImage;
+pub use crate::my_grid::histogram::{Histogram, Luma16Image, RawHistogramError, Rgb16Image};
+pub use crate::my_grid::trig::sincos as fast_sincos;
+pub use crate::tonemap::{Curve, Tonemap};
\ No newline at end of file
@@ -0,0 +1,194 @@
+use std::f32::consts::PI;
+use std::ops::ControlFlow;
+
+use crate::fractal::{FractalMethod, FractalizeParameters};
+use crate::bits::BitSource;
+use crate::my_grid::{Accumulate, for_each_bit_chunk};
+use crate::my_grid::symmetry::SymmetryMaps;
+use crate::my_grid::trig::sincos;
+
+/// Number of orbits advanced in lockstep. Each step of an orbit waits on a long
+/// chain of dependent multiplies in `sincos`, so four AVX registers' worth of
+/// lanes are interleaved to keep the pipeline busy.
+pub(crate) const LANES: usize = 32;
+
+/// The batched counterpart of the scalar loop in `fractalize`.
+///
+/// `LANES` independent orbits all start at `init_x_y`, and each one takes its
+/// branch decisions from its own `u64` of the `num_rands` drawn from `bits`. Every step computes both the
+/// rotation and the polar transform for all lanes, splats both, and keeps the
+/// one picked by the lane's bit as the next point, exactly as the scalar loop does.
+/// The lanes are plain arrays; the per-lane loops, including the mapping to grid
+/// locations, are written without branches so the compiler turns them into SIMD,
+/// with AVX2 when the CPU has it. The writes into the grid stay scalar. In the
+/// `fractalize` bench this is 3-3.7x as fast as the scalar loop at 256², where the
+/// grid stays in cache, 2.2-2.8x at 1024² and only 1.4-1.7x at 4096², where the
+/// scattered writes miss the cache and dominate.
+///
+/// `p.trig` is not used: the polynomial sincos is what lets the lanes vectorise.
+///
+/// The orbits differ from the scalar path's, so only the statistics of the
+/// resulting image match, not the pixels. `SYMMETRIC` also splats the images of `p.symmetry`,
+/// and `observe` is called after every chunk of branch decisions.
+pub(crate) fn fractalize_batched<G: Accumulate, B: BitSource, const SYMMETRIC: bool>(
+    grid: &mut G,
+    p: &FractalizeParameters,
+    bits: &mut B,
+    num_rands: usize,
+    observe: &mut impl FnMut(&G, u64) -> ControlFlow<()>,
+) -> ControlFlow<()>
+{
+    #[cfg(target_arch = "x86_64")]
+    if std::arch::is_x86_feature_detected!("avx2")
+    {
+        // SAFETY: the CPU supports AVX2, checked just above.
+        return unsafe { batched_avx2::<G, B, SYMMETRIC>(grid, p, bits, num_rands, observe) };
+    }
+
+    batched_loop::<G, B, SYMMETRIC>(grid, p, bits, num_rands, observe)
+}
+
+/// [`batched_loop`] compiled for AVX2, so every lane operation works on eight
+/// floats at a time instead of the four of baseline x86-64.
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx2")]
+unsafe fn batched_avx2<G: Accumulate, B: BitSource, const SYMMETRIC: bool>(
+    grid: &mut G,
+    p: &FractalizeParameters,
+    bits: &mut B,
+    num_rands: usize,
+    observe: &mut impl FnMut(&G, u64) -> ControlFlow<()>,
+) -> ControlFlow<()>
+{
+    batched_loop::<G, B, SYMMETRIC>(grid, p, bits, num_rands, observe)
+}
+
+#[inline(always)]
+fn batched_loop<G: Accumulate, B: BitSource, const SYMMETRIC: bool>(
+    grid: &mut G,
+    p: &FractalizeParameters,
+    bits: &mut B,
+    num_rands: usize,
+    observe: &mut impl FnMut(&G, u64) -> ControlFlow<()>,
+) -> ControlFlow<()>
+{
+    let (cols, rows) = grid.dimensions();
+
+    let (rot_sin, rot_cos) = p.rot.sin_cos();
+
+    // theta = y * theta_scale + theta_shift for both methods.
+    let (theta_scale, theta_shift) = match p.method
+    {
+        FractalMethod::Default => (PI, p.theta_offset),
+        FractalMethod::MultiplyTheta => (PI * p.theta_offset, 0.0),
+    };
+
+    // Viewport mapping as in the scalar path, in f32.
+    let x_min = p.viewport.x_min as f32;
+    let y_min = p.viewport.y_min as f32;
+    let x_scale = (1.0 / p.viewport.width()) as f32;
+    let y_scale = (1.0 / p.viewport.height()) as f32;
+
+    let symmetry = SymmetryMaps::<f32>::new(p.symmetry);
+
+    let mut x = [p.init_x_y.0; LANES];
+    let mut y = [p.init_x_y.1; LANES];
+
+    let mut step = |grid: &mut G, bits: &[u64; LANES], active: usize, i: u32|
+    {
+        let mut rot_x = [0.0; LANES];
+        let mut rot_y = [0.0; LANES];
+        let mut polar_x = [0.0; LANES];
+        let mut polar_y = [0.0; LANES];
+
+        for l in 0..LANES
+        {
+            rot_x[l] = x[l] * rot_cos + y[l] * rot_sin;
+            rot_y[l] = y[l] * rot_cos - x[l] * rot_sin;
+
+            let rad = x[l] * 0.5 + 0.5;
+            let (sin, cos) = sincos(y[l] * theta_scale + theta_shift);
+            polar_x[l] = rad * cos;
+            polar_y[l] = rad * sin;
+        }
+
+        // Points outside of the viewport map to u32::MAX, which `splat` ignores.
+        // Written as a select rather than a branch, so it vectorises too.
+        let to_grid = |v: f32, min: f32, scale: f32, n: u32|
+        {
+            let u = (v - min) * scale;
+            let inside = (0.0..1.0).contains(&u);
+            // Only converted when inside, so it is in range. Unlike `as`, this
+            // needs no saturation and becomes a single vector instruction.
+            let loc = unsafe { (if inside { u } else { 0.0 } * n as f32).to_int_unchecked::<i32>() } as u32;
+            if inside { loc } else { u32::MAX }
+        };
+
+        if SYMMETRIC
+        {
+            let mut splat = |x: f32, y: f32| grid.splat(to_grid(x, x_min, x_scale, cols), to_grid(y, y_min, y_scale, rows));
+            for l in 0..active
+            {
+                symmetry.for_each_image(rot_x[l], rot_y[l], &mut splat);
+                symmetry.for_each_image(polar_x[l], polar_y[l], &mut splat);
+            }
+        }
+        else
+        {
+            // Every lane's grid location first, so the mapping runs on whole
+            // lanes, then the scattered writes, which cannot be vectorised.
+            let mut c = [[0; LANES]; 2];
+            let mut r = [[0; LANES]; 2];
+            for l in 0..LANES
+            {
+                c[0][l] = to_grid(rot_x[l], x_min, x_scale, cols);
+                r[0][l] = to_grid(rot_y[l], y_min, y_scale, rows);
+                c[1][l] = to_grid(polar_x[l], x_min, x_scale, cols);
+                r[1][l] = to_grid(polar_y[l], y_min, y_scale, rows);
+            }
+
+            for l in 0..active
+            {
+                grid.splat(c[0][l], r[0][l]);
+                grid.splat(c[1][l], r[1][l]);
+            }
+        }
+
+        for l in 0..LANES
+        {
+            let take_rot = bits[l] & (1 << i) != 0;
+            x[l] = if take_rot { rot_x[l] } else { polar_x[l] };
+            y[l] = if take_rot { rot_y[l] } else { polar_y[l] };
+        }
+    };
+
+    // Chunks are a multiple of LANES long, so only the last one can leave lanes idle.
+    let mut points = 0;
+    for_each_bit_chunk(bits, num_rands, |rands|
+    {
+        let lanes = rands.chunks_exact(LANES);
+        let rest = lanes.remainder();
+
+        for bits in lanes
+        {
+            let bits: &[u64; LANES] = bits.try_into().unwrap();
+            for i in 0..64
+            {
+                step(grid, bits, LANES, i);
+            }
+        }
+
+        if !rest.is_empty()
+        {
+            let mut bits = [0; LANES];
+            bits[..rest.len()].copy_from_slice(rest);
+            for i in 0..64
+            {
+                step(grid, &bits, rest.len(), i);
+            }
+        }
+
+        points += rands.len() as u64 * 64;
+        observe(grid, points)
+    })
+}
@@ -0,0 +1,65 @@
+use std::ops::{Add, Mul, Neg, Sub};
+
+use crate::my_grid::trig::sincos;
+
+/// The float type an orbit is iterated in, see [`crate::Precision`].
+pub(crate) trait OrbitFloat:
+    Copy + PartialOrd + Add<Output = Self> + Sub<Output = Self> + Mul<Output = Self> + Neg<Output = Self>
+{
+    const PI: Self;
+    const ZERO: Self;
+    const HALF: Self;
+    const ONE: Self;
+
+    fn from_f32(v: f32) -> Self;
+    fn from_f64(v: f64) -> Self;
+    fn from_u32(v: u32) -> Self;
+
+    fn sin(self) -> Self;
+    fn cos(self) -> Self;
+    /// The polynomial approximation where one exists, otherwise exact.
+    fn fast_sincos(self) -> (Self, Self);
+
+    /// Truncates a value already known to be in `0..u32::MAX`.
+    ///
+    /// # Safety
+    /// `self` must be finite and within range of `u32`.
+    unsafe fn to_u32_unchecked(self) -> u32;
+}
+
+impl OrbitFloat for f32
+{
+    const PI: Self = std::f32::consts::PI;
+    const ZERO: Self = 0.0;
+    const HALF: Self = 0.5;
+    const ONE: Self = 1.0;
+
+    fn from_f32(v: f32) -> Self { v }
+    fn from_f64(v: f64) -> Self { v as f32 }
+    fn from_u32(v: u32) -> Self { v as f32 }
+
+    fn sin(self) -> Self { f32::sin(self) }
+    fn cos(self) -> Self { f32::cos(self) }
+    fn fast_sincos(self) -> (Self, Self) { sincos(self) }
+
+    unsafe fn to_u32_unchecked(self) -> u32 { self.to_int_unchecked() }
+}
+
+impl OrbitFloat for f64
+{
+    const PI: Self = std::f64::consts::PI;
+    const ZERO: Self = 0.0;
+    const HALF: Self = 0.5;
+    const ONE: Self = 1.0;
+
+    fn from_f32(v: f32) -> Self { v as f64 }
+    fn from_f64(v: f64) -> Self { v }
+    fn from_u32(v: u32) -> Self { v as f64 }
+
+    fn sin(self) -> Self { f64::sin(self) }
+    fn cos(self) -> Self { f64::cos(self) }
+    // An f32 polynomial would throw away the precision f64 is chosen for.
+    fn fast_sincos(self) -> (Self, Self) { self.sin_cos() }
+
+    unsafe fn to_u32_unchecked(self) -> u32 { self.to_int_unchecked() }
+}
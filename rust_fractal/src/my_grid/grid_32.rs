@@ -1,83 +1,211 @@
-use std::f32::consts::PI;
+use std::ops::ControlFlow;
 
-use rand::Rng;
-
-use crate::fractal::Fractalize;
+use crate::bits::BitSource;
+use crate::error::FractalError;
+use crate::fractal::{Evaluation, Fractalize, FractalizeParameters, Precision, Symmetry, TrigMode};
+use crate::my_grid::{Accumulate, for_each_bit_chunk};
+use crate::my_grid::batched::fractalize_batched;
+use crate::my_grid::float::OrbitFloat;
+use crate::my_grid::symmetry::SymmetryMaps;
 
 pub type MyColorImage = image::ImageBuffer<image::Rgba<u8>, Vec<u8>>;
 
-impl Fractalize for MyColorImage
+impl Accumulate for MyColorImage
+{
+    fn dimensions(&self) -> (u32, u32)
+    {
+        image::ImageBuffer::dimensions(self)
+    }
+
+    // Called from the generic render loops in other crates, once per sample.
+    #[inline]
+    fn splat(&mut self, c: u32, r: u32)
+    {
+        if let Some(p) = self.get_pixel_mut_checked(c, r)
+        {
+            // Testiing showed that using add(..) may be faster, but not
+            // if we want to check for overflow.
+            // This prevents a potential panic on overflow and allows over-exposure.
+            //
+            // Potential target for simd optimization?
+            p[0] = p[0].checked_add(1).unwrap_or(p[0]);
+            p[1] = p[1].checked_add(1).unwrap_or(p[1]);
+            p[2] = p[2].checked_add(1).unwrap_or(p[2]);
+        }
+    }
+}
+
+impl<G: Accumulate> Fractalize for G
 {
-    fn fractalize(&mut self, p: crate::fractal::FractalizeParameters) -> () 
+    fn fractalize_observed<B: BitSource>(
+        &mut self,
+        p: FractalizeParameters,
+        mut bits: B,
+        mut observe: impl FnMut(&Self, u64) -> ControlFlow<()>,
+    ) -> Result<(), FractalError>
     {
-        let (mut x, mut y) = p.init_x_y();
+        p.validate()?;
+
+        let (cols, rows) = self.dimensions();
+        if cols == 0 || rows == 0
+        {
+            return Err(FractalError::ZeroSized { width: cols, height: rows });
+        }
+
         let max_points = p.max_points();
-        
-        let rot = p.rot();
-        let rot_cos = rot.cos();
-        let rot_sin = rot.sin();
 
-        let theta_offset = p.theta_offset();
+        let num_rands = (max_points / 64) as usize;
 
-        let _method = *p.method();
+        // Without symmetry the extra images are compiled out; checking for them
+        // on every splat costs a noticeable share of the loop.
+        let symmetric = *p.symmetry() != Symmetry::NONE;
 
-        let distr = 
-            rand::distr::Uniform::new(0, u64::MAX).unwrap();
-        let rands: Vec<u64> = rand::rng().sample_iter(&distr).take((max_points / 64) as usize).collect();
+        let (bits, observe) = (&mut bits, &mut observe);
+        // `p.trig` only applies to the scalar paths. The batched one always uses the
+        // polynomial sincos, as documented on `Evaluation::Batched`.
+        let flow = match (*p.precision(), *p.evaluation(), symmetric)
+        {
+            (Precision::F64, _, false) => fractalize_scalar::<f64, _, _, false>(self, &p, bits, num_rands, observe),
+            (Precision::F64, _, true) => fractalize_scalar::<f64, _, _, true>(self, &p, bits, num_rands, observe),
+            (Precision::F32, Evaluation::Batched, false) => fractalize_batched::<_, _, false>(self, &p, bits, num_rands, observe),
+            (Precision::F32, Evaluation::Batched, true) => fractalize_batched::<_, _, true>(self, &p, bits, num_rands, observe),
+            (Precision::F32, Evaluation::Scalar, false) => fractalize_scalar::<f32, _, _, false>(self, &p, bits, num_rands, observe),
+            (Precision::F32, Evaluation::Scalar, true) => fractalize_scalar::<f32, _, _, true>(self, &p, bits, num_rands, observe),
+        };
+
+        match flow
+        {
+            ControlFlow::Continue(()) => Ok(()),
+            ControlFlow::Break(()) => Err(FractalError::Cancelled),
+        }
+    }
+}
+
+/// One orbit, advanced one point at a time in the float type `F`.
+/// `num_rands` u64s of branch decisions are drawn from `bits` as the orbit goes,
+/// and `observe` is called after each chunk of them.
+/// `SYMMETRIC` also splats the images of `p.symmetry`.
+fn fractalize_scalar<F: OrbitFloat, G: Accumulate, B: BitSource, const SYMMETRIC: bool>(
+    grid: &mut G,
+    p: &FractalizeParameters,
+    bits: &mut B,
+    num_rands: usize,
+    observe: &mut impl FnMut(&G, u64) -> ControlFlow<()>,
+) -> ControlFlow<()>
+{
+    let (x, y) = p.init_x_y();
+    let (mut x, mut y) = (F::from_f32(x), F::from_f32(y));
+    
+    let rot = F::from_f32(p.rot());
+    let rot_cos = rot.cos();
+    let rot_sin = rot.sin();
 
+    let theta_offset = F::from_f32(p.theta_offset());
 
-        let rows = self.height();
-        let cols = self.width();
+    let _method = *p.method();
+    let trig = *p.trig();
 
-        let transform = 
-        move |x: f32, y: f32, s: bool|
+    let (cols, rows) = grid.dimensions();
+
+    let viewport = *p.viewport();
+    let x_min = F::from_f64(viewport.x_min);
+    let y_min = F::from_f64(viewport.y_min);
+    let x_scale = F::from_f64(1.0 / viewport.width());
+    let y_scale = F::from_f64(1.0 / viewport.height());
+
+    let symmetry = SymmetryMaps::<F>::new(*p.symmetry());
+
+    let transform = 
+    move |x: F, y: F, s: bool|
+    {
+        let (x, y) = 
+        if s
+        {
+            (
+                x * rot_cos + y * rot_sin,
+                y * rot_cos - x * rot_sin
+            )
+        }
+        else
         {
-            let (x, y) = 
-            if s
+            let rad = x * F::HALF + F::HALF;
+            // let theta: f32 = y * PI + theta_offset;
+
+            use crate::fractal::FractalMethod::*;
+            let theta: F = match _method
             {
-                (
-                    x * rot_cos + y * rot_sin,
-                    y * rot_cos - x * rot_sin
-                )
-            }
-            else
+                Default => y * F::PI + theta_offset,
+                MultiplyTheta => y * F::PI * theta_offset,
+            };
+            match trig
             {
-                let rad = x * 0.5 + 0.5;
-                // let theta: f32 = y * PI + theta_offset;
-
-                use crate::fractal::FractalMethod::*;
-                let theta: f32 = match _method
-                {
-                    Default => y * PI + theta_offset,
-                    MultiplyTheta => y * PI * theta_offset,
-                };
+                TrigMode::Exact =>
                 (
                     rad * theta.cos(),
                     rad * theta.sin()
-                )
-            };
-
-            (x, y)
+                ),
+                TrigMode::Fast =>
+                {
+                    let (sin, cos) = theta.fast_sincos();
+                    (rad * cos, rad * sin)
+                },
+            }
         };
 
-        let xy_to_grid_loc =
-        move |x: f32, y: f32| -> (u32, u32)
+        (x, y)
+    };
+
+    let xy_to_grid_loc =
+    move |x: F, y: F| -> Option<(u32, u32)>
+    {
+        // Position within the viewport, in [0, 1) when visible.
+        // For the default [-1, 1] viewport this is exactly `x * 0.5 + 0.5`.
+        let v = (y - y_min) * y_scale;
+        let u = (x - x_min) * x_scale;
+        if !(u >= F::ZERO && u < F::ONE && v >= F::ZERO && v < F::ONE)
+        {
+            return None;
+        }
+
+        let r = v * F::from_u32(rows);
+        let c = u * F::from_u32(cols);
+
+        // Testing showed that this is faster than using the as operator.
+        // Normally rust has protection against floats being too large to fit in an int,
+        // but the check above keeps the values in range 0..width
+        unsafe {
+            Some((r.to_u32_unchecked(), c.to_u32_unchecked()))
+        }
+    };
+
+    let splat_images =
+    move |grid: &mut G, x: F, y: F|
+    {
+        let mut splat = |x: F, y: F|
         {
-            let r: f32 = (y * 0.5 + 0.5) * rows as f32;
-            let c: f32 = (x * 0.5 + 0.5) * cols as f32;
-
-            // Testing showed that this is faster than using the as operator.
-            // Normally rust has protection against floats being too large to fit in an int,
-            // but in this case we know the values will be in range 0..width
-            unsafe {
-                (r.to_int_unchecked(), c.to_int_unchecked())
+            if let Some((r, c)) = xy_to_grid_loc(x, y)
+            {
+                grid.splat(c, r);
             }
         };
 
-        let _do_both_transformations =
-        ||
+        if SYMMETRIC
+        {
+            symmetry.for_each_image(x, y, splat);
+        }
+        else
         {
-            for rr in rands
+            splat(x, y);
+        }
+    };
+
+    let mut points = 0;
+    let mut _do_both_transformations =
+    ||
+    {
+        for_each_bit_chunk(bits, num_rands, |rands|
+        {
+            for &rr in rands
             {
                 for i in 0..64_u64
                 {
@@ -85,34 +213,20 @@ impl Fractalize for MyColorImage
 
                     // first
                     let (xx, yy) = transform(x, y, this_r == 0);
-                    let (r, c) = xy_to_grid_loc(xx, yy);
-                    if let Some(p) = self.get_pixel_mut_checked(c, r)
-                    {
-                        // Testiing showed that using add(..) may be faster, but not
-                        // if we want to check for overflow.
-                        // This prevents a potential panic on overflow and allows over-exposure.
-                        //
-                        // Potential target for simd optimization?
-                        p[0] = p[0].checked_add(1).unwrap_or(p[0]);
-                        p[1] = p[1].checked_add(1).unwrap_or(p[1]);
-                        p[2] = p[2].checked_add(1).unwrap_or(p[2]);
-                    }
+                    splat_images(grid, xx, yy);
 
                     // second
                     let (xx, yy) = transform(x, y, this_r != 0);
-                    let (r, c) = xy_to_grid_loc(xx, yy);
-                    if let Some(p) = self.get_pixel_mut_checked(c, r)
-                    {
-                        p[0] = p[0].checked_add(1).unwrap_or(p[0]);
-                        p[1] = p[1].checked_add(1).unwrap_or(p[1]);
-                        p[2] = p[2].checked_add(1).unwrap_or(p[2]);
-                    }
+                    splat_images(grid, xx, yy);
 
                     (x, y) = (xx, yy);
                 }
             }
-        };
-        
-        _do_both_transformations();
-    }
-}
\ No newline at end of file
+
+            points += rands.len() as u64 * 64;
+            observe(grid, points)
+        })
+    };
+    
+    _do_both_transformations()
+}
@@ -0,0 +1,282 @@
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+use crate::my_grid::Accumulate;
+
+/// 16-bit grayscale image, see [`Histogram::to_luma16`].
+pub type Luma16Image = image::ImageBuffer<image::Luma<u16>, Vec<u16>>;
+/// 16-bit RGB image, see [`Histogram::to_rgb16`].
+pub type Rgb16Image = image::ImageBuffer<image::Rgb<u16>, Vec<u16>>;
+
+/// A wide accumulator holding the raw hit count of every pixel.
+///
+/// [`crate::MyColorImage`] saturates at 255 hits, which bands badly once levels
+/// are pushed around. A histogram keeps the full `u32` count, so it can be
+/// exported at 16 bits or dumped raw for processing in other tools.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Histogram
+{
+    width: u32,
+    height: u32,
+    counts: Vec<u32>,
+}
+
+impl Histogram
+{
+    /// Magic bytes at the start of a raw histogram dump.
+    pub const RAW_MAGIC: [u8; 8] = *b"RFHIST\0\0";
+    /// Version of the raw dump layout written by [`Histogram::write_raw`].
+    pub const RAW_VERSION: u32 = 1;
+
+    pub fn new(width: u32, height: u32) -> Self
+    {
+        Self
+        {
+            width,
+            height,
+            counts: vec![0; width as usize * height as usize],
+        }
+    }
+
+    pub fn width(&self) -> u32
+    {
+        self.width
+    }
+
+    pub fn height(&self) -> u32
+    {
+        self.height
+    }
+
+    /// Hit counts in row-major order.
+    pub fn counts(&self) -> &[u32]
+    {
+        &self.counts
+    }
+
+    pub fn get(&self, c: u32, r: u32) -> Option<u32>
+    {
+        (c < self.width && r < self.height)
+        .then(|| self.counts[r as usize * self.width as usize + c as usize])
+    }
+
+    pub fn max_count(&self) -> u32
+    {
+        self.counts.iter().copied().max().unwrap_or(0)
+    }
+
+    pub fn clear(&mut self)
+    {
+        self.counts.fill(0);
+    }
+
+    /// One count per level, saturating at `u16::MAX`.
+    /// This is the 16-bit counterpart of how [`crate::MyColorImage`] is filled.
+    pub fn to_luma16(&self) -> Luma16Image
+    {
+        let data = self.counts.iter().map(|&n| n.min(u16::MAX as u32) as u16).collect();
+
+        Luma16Image::from_raw(self.width, self.height, data)
+        .expect("histogram buffer matches its dimensions")
+    }
+
+    /// Same levels as [`Histogram::to_luma16`], repeated across the three channels.
+    pub fn to_rgb16(&self) -> Rgb16Image
+    {
+        let data = self.counts.iter()
+            .flat_map(|&n| [n.min(u16::MAX as u32) as u16; 3])
+            .collect();
+
+        Rgb16Image::from_raw(self.width, self.height, data)
+        .expect("histogram buffer matches its dimensions")
+    }
+
+    /// Writes the raw counts: [`Histogram::RAW_MAGIC`], then the version, width and
+    /// height as little-endian `u32`, then every count as a little-endian `u32` in row-major order.
+    pub fn write_raw(&self, mut w: impl Write) -> std::io::Result<()>
+    {
+        w.write_all(&Self::RAW_MAGIC)?;
+        w.write_all(&Self::RAW_VERSION.to_le_bytes())?;
+        w.write_all(&self.width.to_le_bytes())?;
+        w.write_all(&self.height.to_le_bytes())?;
+        self.write_counts(&mut w)?;
+
+        w.flush()
+    }
+
+    pub fn save_raw(&self, path: impl AsRef<Path>) -> std::io::Result<()>
+    {
+        self.write_raw(BufWriter::new(File::create(path)?))
+    }
+
+    /// Reads a dump written by [`Histogram::write_raw`].
+    ///
+    /// The magic and version are checked first, then the number of counts must
+    /// match the stored width and height exactly.
+    pub fn read_raw(mut r: impl Read) -> Result<Self, RawHistogramError>
+    {
+        let mut magic = [0; 8];
+        r.read_exact(&mut magic)?;
+        if magic != Self::RAW_MAGIC
+        {
+            return Err(RawHistogramError::BadMagic);
+        }
+
+        let mut read_u32 = || -> std::io::Result<u32>
+        {
+            let mut buf = [0; 4];
+            r.read_exact(&mut buf)?;
+            Ok(u32::from_le_bytes(buf))
+        };
+
+        let version = read_u32()?;
+        if version != Self::RAW_VERSION
+        {
+            return Err(RawHistogramError::UnsupportedVersion(version));
+        }
+
+        let width = read_u32()?;
+        let height = read_u32()?;
+        if width == 0 || height == 0
+        {
+            return Err(RawHistogramError::ZeroSized);
+        }
+
+        let mut bytes = Vec::new();
+        r.read_to_end(&mut bytes)?;
+
+        let expected_bytes = (width as usize)
+            .checked_mul(height as usize)
+            .and_then(|n| n.checked_mul(4));
+        if expected_bytes != Some(bytes.len())
+        {
+            return Err(RawHistogramError::SizeMismatch { width, height, actual_bytes: bytes.len() });
+        }
+
+        let counts = bytes
+            .chunks_exact(4)
+            .map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+            .collect();
+
+        Ok(Self { width, height, counts })
+    }
+
+    pub fn load_raw(path: impl AsRef<Path>) -> Result<Self, RawHistogramError>
+    {
+        Self::read_raw(BufReader::new(File::open(path)?))
+    }
+
+    /// Writes the counts as a NumPy `.npy` array of shape `(height, width)` and dtype `<u4`.
+    pub fn write_npy(&self, mut w: impl Write) -> std::io::Result<()>
+    {
+        const MAGIC: &[u8] = b"\x93NUMPY\x01\x00";
+
+        let mut header = format!(
+            "{{'descr': '<u4', 'fortran_order': False, 'shape': ({}, {}), }}",
+            self.height, self.width
+        );
+        // The magic, the header length and the header itself are padded to a multiple of 64,
+        // and the header always ends in a newline.
+        let unpadded = MAGIC.len() + 2 + header.len() + 1;
+        header.extend(std::iter::repeat_n(' ', unpadded.next_multiple_of(64) - unpadded));
+        header.push('\n');
+
+        w.write_all(MAGIC)?;
+        w.write_all(&(header.len() as u16).to_le_bytes())?;
+        w.write_all(header.as_bytes())?;
+        self.write_counts(&mut w)?;
+
+        w.flush()
+    }
+
+    pub fn save_npy(&self, path: impl AsRef<Path>) -> std::io::Result<()>
+    {
+        self.write_npy(BufWriter::new(File::create(path)?))
+    }
+
+    fn write_counts(&self, w: &mut impl Write) -> std::io::Result<()>
+    {
+        for n in &self.counts
+        {
+            w.write_all(&n.to_le_bytes())?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Accumulate for Histogram
+{
+    fn dimensions(&self) -> (u32, u32)
+    {
+        (self.width, self.height)
+    }
+
+    // Called from the generic render loops in other crates, once per sample.
+    #[inline]
+    fn splat(&mut self, c: u32, r: u32)
+    {
+        if c < self.width && r < self.height
+        {
+            let n = &mut self.counts[r as usize * self.width as usize + c as usize];
+            *n = n.saturating_add(1);
+        }
+    }
+}
+
+/// Why a raw histogram dump could not be loaded.
+#[derive(Debug)]
+pub enum RawHistogramError
+{
+    Io(std::io::Error),
+    /// The data does not start with [`Histogram::RAW_MAGIC`].
+    BadMagic,
+    /// The dump was written by a newer or unknown layout version.
+    UnsupportedVersion(u32),
+    /// The header claims a width or height of zero.
+    ZeroSized,
+    /// The number of count bytes does not match the header's width and height.
+    SizeMismatch
+    {
+        width: u32,
+        height: u32,
+        actual_bytes: usize,
+    },
+}
+
+impl std::fmt::Display for RawHistogramError
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result
+    {
+        match self
+        {
+            Self::Io(e) => write!(f, "could not read histogram: {e}"),
+            Self::BadMagic => write!(f, "not a raw histogram dump"),
+            Self::UnsupportedVersion(v) => write!(f, "unsupported histogram version {v}, expected {}", Histogram::RAW_VERSION),
+            Self::ZeroSized => write!(f, "histogram has a zero width or height"),
+            Self::SizeMismatch { width, height, actual_bytes } =>
+                write!(f, "histogram is {width}x{height} but holds {actual_bytes} bytes of counts"),
+        }
+    }
+}
+
+impl std::error::Error for RawHistogramError
+{
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)>
+    {
+        match self
+        {
+            Self::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for RawHistogramError
+{
+    fn from(e: std::io::Error) -> Self
+    {
+        Self::Io(e)
+    }
+}
@@ -1 +1,53 @@
-pub mod grid_32;
\ No newline at end of file
+mod batched;
+mod float;
+pub mod grid_32;
+pub mod histogram;
+mod symmetry;
+pub(crate) mod trig;
+
+use std::ops::ControlFlow;
+
+use crate::bits::BitSource;
+
+/// How many u64s of branch decisions are drawn from a [`BitSource`] at a time.
+/// A multiple of `batched::LANES`, so chunking does not change which lanes the
+/// batched path hands each u64 to.
+const BIT_CHUNK: usize = 4096;
+const _: () = assert!(BIT_CHUNK % batched::LANES == 0);
+
+/// Draws `count` u64s of branch decisions from `bits` and hands them to `f` in
+/// order, at most [`BIT_CHUNK`] at a time, so memory use does not grow with the
+/// number of points. Stops early if `f` breaks.
+pub(crate) fn for_each_bit_chunk<B: BitSource>(
+    bits: &mut B,
+    count: usize,
+    mut f: impl FnMut(&[u64]) -> ControlFlow<()>,
+) -> ControlFlow<()>
+{
+    let mut chunk = [0_u64; BIT_CHUNK];
+    let mut left = count;
+
+    while left > 0
+    {
+        let n = left.min(BIT_CHUNK);
+        for b in &mut chunk[..n]
+        {
+            *b = bits.next_bits();
+        }
+        f(&chunk[..n])?;
+        left -= n;
+    }
+
+    ControlFlow::Continue(())
+}
+
+/// A grid that orbit samples are splatted into by [`crate::Fractalize`].
+pub trait Accumulate
+{
+    /// `(width, height)` of the grid.
+    fn dimensions(&self) -> (u32, u32);
+
+    /// Records one sample at column `c`, row `r`.
+    /// Samples outside of the grid are ignored.
+    fn splat(&mut self, c: u32, r: u32);
+}
\ No newline at end of file
@@ -0,0 +1,49 @@
+use crate::fractal::Symmetry;
+use crate::my_grid::float::OrbitFloat;
+
+/// The maps of a [`Symmetry`], precomputed in the orbit's float type.
+pub(crate) struct SymmetryMaps<F>
+{
+    /// `(cos, sin)` of every rotation but the identity.
+    rotations: Vec<(F, F)>,
+    mirror: bool,
+}
+
+impl<F: OrbitFloat> SymmetryMaps<F>
+{
+    pub(crate) fn new(symmetry: Symmetry) -> Self
+    {
+        let rotations = (1..symmetry.order)
+            .map(|k|
+            {
+                let angle = std::f64::consts::TAU * k as f64 / symmetry.order as f64;
+                (F::from_f64(angle.cos()), F::from_f64(angle.sin()))
+            })
+            .collect();
+
+        Self { rotations, mirror: symmetry.mirror }
+    }
+
+    /// Calls `f` with `(x, y)` and each of its symmetric images.
+    #[inline]
+    pub(crate) fn for_each_image(&self, x: F, y: F, mut f: impl FnMut(F, F))
+    {
+        f(x, y);
+        if self.mirror
+        {
+            f(x, -y);
+        }
+
+        for &(cos, sin) in &self.rotations
+        {
+            let (rx, ry) = (x * cos - y * sin, x * sin + y * cos);
+            f(rx, ry);
+            if self.mirror
+            {
+                // Rotating the mirror image rather than mirroring the rotated
+                // point gives the same set of images.
+                f(x * cos + y * sin, x * sin - y * cos);
+            }
+        }
+    }
+}
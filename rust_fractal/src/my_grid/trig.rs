@@ -0,0 +1,40 @@
+use std::f32::consts::{FRAC_PI_2, PI, TAU};
+
+/// Branch-free polynomial `(sin(theta), cos(theta))`.
+///
+/// `theta` is reduced to [-PI, PI] and reflected into [-PI/2, PI/2], where
+/// Taylor polynomials of degree 11 (sin) and 12 (cos) are evaluated.
+/// The absolute error is below `5e-7` for `|theta| <= 4 * PI`, which covers
+/// every angle the renderer produces for offsets in [-PI, PI]. Past that the
+/// range reduction slowly loses precision, to about `1e-5` at `64 * PI`.
+///
+/// There are no branches or table lookups, so a loop over lanes of this
+/// function is vectorised by the compiler.
+#[inline(always)]
+pub fn sincos(theta: f32) -> (f32, f32)
+{
+    // Reduce to [-PI, PI]. TAU is split Cody-Waite style so that `k * TAU_HI`
+    // is exact and the reduction does not lose the low bits of theta.
+    const TAU_HI: f32 = 6.281_25;
+    const TAU_LO: f32 = TAU - TAU_HI;
+
+    // Adding and removing 1.5 * 2^23 rounds to the nearest integer. Unlike `round`
+    // this is not a libm call on baseline x86-64, so it does not block vectorisation.
+    const ROUND: f32 = 12_582_912.0;
+    let k = (theta * (1.0 / TAU) + ROUND) - ROUND;
+    let t = (theta - k * TAU_HI) - k * TAU_LO;
+
+    // Reflect into [-PI/2, PI/2]: sin(PI - t) = sin(t), cos(PI - t) = -cos(t).
+    let reflect = t.abs() > FRAC_PI_2;
+    let t = if reflect { PI.copysign(t) - t } else { t };
+    let cos_sign = if reflect { -1.0 } else { 1.0 };
+
+    let t2 = t * t;
+
+    let sin = t * (1.0 + t2 * (-1.0 / 6.0 + t2 * (1.0 / 120.0 + t2 * (-1.0 / 5040.0
+        + t2 * (1.0 / 362_880.0 + t2 * (-1.0 / 39_916_800.0))))));
+    let cos = 1.0 + t2 * (-0.5 + t2 * (1.0 / 24.0 + t2 * (-1.0 / 720.0
+        + t2 * (1.0 / 40_320.0 + t2 * (-1.0 / 3_628_800.0 + t2 * (1.0 / 479_001_600.0))))));
+
+    (sin, cos * cos_sign)
+}
@@ -0,0 +1,131 @@
+use derive_setters::*;
+
+use crate::my_grid::grid_32::MyColorImage;
+use crate::my_grid::histogram::{Histogram, Rgb16Image};
+
+/// Shape of the response curve from hit count to brightness.
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Curve
+{
+    /// Brightness is proportional to exposed count.
+    #[default]
+    Linear,
+    /// `v^(1 / gamma)`; gammas above 1 lift the shadows.
+    Gamma,
+    /// Logarithmic response, keeps detail in both sparse and dense areas.
+    Log,
+}
+
+/// Turns a [`Histogram`] into a displayable image.
+///
+/// The default reproduces what rendering straight into a [`MyColorImage`] gives:
+/// one level per hit, saturating at 255, black to white.
+#[derive(Setters, Clone, Copy, Debug, PartialEq)]
+#[setters(prefix = "with_")]
+pub struct Tonemap
+{
+    /// Counts are multiplied by this before the curve is applied.
+    pub exposure: f32,
+    pub curve: Curve,
+    /// Only used by [`Curve::Gamma`].
+    pub gamma: f32,
+    /// Color of pixels with no hits.
+    pub low: [u8; 3],
+    /// Color of saturated pixels.
+    pub high: [u8; 3],
+}
+
+impl Default for Tonemap
+{
+    fn default() -> Self
+    {
+        Self
+        {
+            exposure: 1.0,
+            curve: Curve::default(),
+            gamma: 2.2,
+            low: Self::BLACK,
+            high: Self::WHITE,
+        }
+    }
+}
+
+impl Tonemap
+{
+    pub const BLACK: [u8; 3] = [0x00, 0x00, 0x00];
+    pub const WHITE: [u8; 3] = [0xff, 0xff, 0xff];
+
+    /// Brightness in [0, 1] for a hit count.
+    pub fn level(&self, count: u32) -> f32
+    {
+        // 255 exposed hits are full brightness, matching MyColorImage.
+        const FULL: f32 = 255.0;
+
+        let exposed = count as f32 * self.exposure;
+        let v = match self.curve
+        {
+            Curve::Linear => exposed / FULL,
+            Curve::Gamma => (exposed / FULL).max(0.0).powf(1.0 / self.gamma),
+            Curve::Log => exposed.max(0.0).ln_1p() / FULL.ln_1p(),
+        };
+
+        v.clamp(0.0, 1.0)
+    }
+
+    /// Maps every count through the curve and blends from `low` to `high`.
+    /// The result is fully opaque.
+    pub fn apply(&self, histogram: &Histogram) -> MyColorImage
+    {
+        // Counts repeat heavily, and the low ones most of all, so cache the first levels.
+        let lut: Vec<[u8; 3]> = (0..=u16::MAX as u32).map(|n| self.color(n)).collect();
+
+        let mut img = MyColorImage::new(histogram.width(), histogram.height());
+        for (p, &n) in img.pixels_mut().zip(histogram.counts())
+        {
+            let [r, g, b] = lut.get(n as usize).copied().unwrap_or_else(|| self.color(n));
+            p.0 = [r, g, b, 0xff];
+        }
+
+        img
+    }
+
+    /// Like [`Tonemap::apply`], at 16 bits per channel, for smooth gradients
+    /// when the image is processed further.
+    pub fn apply_16(&self, histogram: &Histogram) -> Rgb16Image
+    {
+        let lut: Vec<[u16; 3]> = (0..=u16::MAX as u32).map(|n| self.color_16(n)).collect();
+
+        let mut img = Rgb16Image::new(histogram.width(), histogram.height());
+        for (p, &n) in img.pixels_mut().zip(histogram.counts())
+        {
+            p.0 = lut.get(n as usize).copied().unwrap_or_else(|| self.color_16(n));
+        }
+
+        img
+    }
+
+    fn color(&self, count: u32) -> [u8; 3]
+    {
+        let v = self.level(count);
+        let mix = |lo: u8, hi: u8| (lo as f32 + (hi as f32 - lo as f32) * v).round() as u8;
+
+        [
+            mix(self.low[0], self.high[0]),
+            mix(self.low[1], self.high[1]),
+            mix(self.low[2], self.high[2]),
+        ]
+    }
+
+    fn color_16(&self, count: u32) -> [u16; 3]
+    {
+        let v = self.level(count);
+        // 257 maps 0xff to 0xffff.
+        let mix = |lo: u8, hi: u8| (lo as f32 * 257.0 + (hi as f32 - lo as f32) * 257.0 * v).round() as u16;
+
+        [
+            mix(self.low[0], self.high[0]),
+            mix(self.low[1], self.high[1]),
+            mix(self.low[2], self.high[2]),
+        ]
+    }
+}
@@ -0,0 +1,192 @@
+//! Keyframe sampling and animation export.
+
+mod common;
+
+use std::fs::File;
+use std::io::BufReader;
+
+use image::AnimationDecoder;
+use image::codecs::gif::GifDecoder;
+use image::codecs::png::PngDecoder;
+
+use rust_fractal::{AnimationSpec, Easing, FractalError, FractalizeParameters, Keyframe};
+use common::scratch_dir;
+
+/// Three keyframes one second apart, at 10 fps so they land on frames 0, 10 and 20.
+fn spec() -> AnimationSpec
+{
+    AnimationSpec::default()
+        .with_keyframes(vec![
+            Keyframe::new(0.0, 0.1, 0.2),
+            Keyframe::new(1.0, 0.5, -0.3),
+            Keyframe::new(2.0, 1.0, 0.7),
+        ])
+        .with_fps(10.0)
+        .with_frame_count(21)
+}
+
+/// A spec small and short enough to export in a test.
+fn tiny() -> AnimationSpec
+{
+    spec()
+        .with_frame_count(3)
+        .with_width(16)
+        .with_height(16)
+        .with_params(FractalizeParameters::default().with_max_points(6400))
+}
+
+fn rot_theta(spec: &AnimationSpec, frame: u32) -> (f32, f32)
+{
+    let p = spec.params_at(frame);
+    (p.rot, p.theta_offset)
+}
+
+#[test]
+fn keyframes_are_hit_exactly()
+{
+    let spec = spec();
+    assert_eq!(rot_theta(&spec, 0), (0.1, 0.2));
+    assert_eq!(rot_theta(&spec, 10), (0.5, -0.3));
+    assert_eq!(rot_theta(&spec, 20), (1.0, 0.7));
+    assert_eq!(spec.params_at(7).seed, Some(spec.seed));
+}
+
+#[test]
+fn values_are_held_outside_the_keyframes()
+{
+    let spec = spec().with_keyframes(vec![Keyframe::new(0.5, 0.1, 0.2), Keyframe::new(1.0, 0.5, -0.3)]);
+    assert_eq!(rot_theta(&spec, 0), (0.1, 0.2));
+    assert_eq!(rot_theta(&spec, 3), (0.1, 0.2));
+    assert_eq!(rot_theta(&spec, 11), (0.5, -0.3));
+    assert_eq!(rot_theta(&spec, 100), (0.5, -0.3));
+}
+
+#[test]
+fn easings_hit_their_midpoints()
+{
+    let with_easing = |easing: Easing|
+    {
+        let mut spec = spec();
+        spec.keyframes[0].easing = easing;
+        rot_theta(&spec, 5)
+    };
+
+    assert_eq!(with_easing(Easing::Hold), (0.1, 0.2));
+
+    let (rot, theta) = with_easing(Easing::EaseInOut);
+    assert!((rot - 0.3).abs() < 1e-6 && (theta + 0.05).abs() < 1e-6, "{rot} {theta}");
+
+    // Ease in is behind linear at the midpoint, ease out ahead.
+    assert!(with_easing(Easing::EaseIn).0 < with_easing(Easing::Linear).0);
+    assert!(with_easing(Easing::EaseOut).0 > with_easing(Easing::Linear).0);
+}
+
+#[test]
+fn written_frames_are_not_rendered_again()
+{
+    let dir = scratch_dir("write_frames");
+    let spec = tiny();
+
+    assert_eq!(spec.write_frames(&dir).unwrap(), 3);
+    assert_eq!(spec.write_frames(&dir).unwrap(), 0);
+    for frame in 0..3
+    {
+        assert!(dir.join(AnimationSpec::frame_file_name(frame)).exists());
+    }
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn leftover_partial_frames_are_rendered_again()
+{
+    let dir = scratch_dir("partial_frames");
+    let first = dir.join(AnimationSpec::frame_file_name(0));
+    std::fs::write(first.with_extension("png.part"), b"interrupted").unwrap();
+
+    assert_eq!(tiny().write_frames(&dir).unwrap(), 3);
+    assert!(image::open(&first).is_ok());
+    assert!(!first.with_extension("png.part").exists());
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn frame_rates_without_a_delay_are_rejected()
+{
+    let dir = scratch_dir("fps");
+
+    // A subnormal rate used to overflow the frame delay and panic.
+    for fps in [0.0, -1.0, f32::NAN, f32::INFINITY, f32::MIN_POSITIVE / 2.0, 0.001]
+    {
+        let spec = tiny().with_fps(fps);
+        assert!(matches!(spec.validate(), Err(FractalError::InvalidAnimation(_))), "{fps}");
+        assert!(spec.write_gif(dir.join("slow.gif")).is_err(), "{fps}");
+    }
+    assert!(tiny().with_fps(AnimationSpec::MIN_FPS).validate().is_ok());
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+/// Delays of the decoded frames, in milliseconds.
+fn delays_ms(frames: image::Frames<'_>) -> Vec<u32>
+{
+    frames
+        .map(|frame|
+        {
+            let frame = frame.unwrap();
+            assert_eq!(frame.buffer().dimensions(), (16, 16));
+            let (numer, denom) = frame.delay().numer_denom_ms();
+            numer / denom
+        })
+        .collect()
+}
+
+#[test]
+fn gif_loops_forever()
+{
+    let dir = scratch_dir("gif");
+    let path = dir.join("loop.gif");
+    tiny().write_gif(&path).unwrap();
+
+    let decoder = GifDecoder::new(BufReader::new(File::open(&path).unwrap())).unwrap();
+    assert_eq!(delays_ms(decoder.into_frames()), [100; 3]);
+
+    // The NETSCAPE2.0 application extension with a loop count of 0, meaning forever.
+    let bytes = std::fs::read(&path).unwrap();
+    assert!(bytes.windows(16).any(|w| w == b"NETSCAPE2.0\x03\x01\x00\x00\x00"));
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn apng_loops_forever()
+{
+    let dir = scratch_dir("apng");
+    let path = dir.join("loop.png");
+    tiny().write_apng(&path).unwrap();
+
+    let decoder = PngDecoder::new(BufReader::new(File::open(&path).unwrap())).unwrap();
+    assert!(decoder.is_apng().unwrap());
+    assert_eq!(delays_ms(decoder.apng().unwrap().into_frames()), [100; 3]);
+
+    let reader = png::Decoder::new(BufReader::new(File::open(&path).unwrap())).read_info().unwrap();
+    let control = reader.info().animation_control.unwrap();
+    assert_eq!((control.num_frames, control.num_plays), (3, 0));
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn failed_exports_leave_nothing_behind()
+{
+    let dir = scratch_dir("failed_export");
+    // Every frame is rejected, after the file has been started.
+    let spec = tiny().with_params(FractalizeParameters::default().with_max_points(10));
+
+    assert!(spec.write_gif(dir.join("loop.gif")).is_err());
+    assert!(spec.write_apng(dir.join("loop.png")).is_err());
+    assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 0);
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,68 @@
+//! The batched path follows different orbits than the scalar one, so instead
+//! of comparing pixels these tests compare the normalised density of hits.
+
+mod common;
+
+use std::f32::consts::PI;
+
+use rust_fractal::{Evaluation, FractalMethod, Fractalize, FractalizeParameters, Histogram};
+use common::density_distance;
+
+const SIZE: u32 = 64;
+
+fn render(params: FractalizeParameters) -> Histogram
+{
+    let mut h = Histogram::new(SIZE, SIZE);
+    h.fractalize(params).unwrap();
+    h
+}
+
+fn assert_batched_matches_scalar(params: FractalizeParameters)
+{
+    let params = params.with_max_points(4_000_000);
+
+    let scalar = render(params.with_seed(Some(1)));
+    let scalar_other_seed = render(params.with_seed(Some(2)));
+    let batched = render(params.with_seed(Some(3)).with_evaluation(Evaluation::Batched));
+
+    // Two scalar renders differ only by sampling noise; the batched render
+    // should not be meaningfully further away than that.
+    let noise = density_distance(scalar.counts(), scalar_other_seed.counts());
+    let distance = density_distance(scalar.counts(), batched.counts());
+
+    assert!(
+        distance < 0.02 && distance < 3.0 * noise.max(0.002),
+        "batched density is {distance:.4} away from scalar, sampling noise is {noise:.4}"
+    );
+}
+
+#[test]
+fn batched_matches_scalar_default()
+{
+    assert_batched_matches_scalar(FractalizeParameters::default());
+}
+
+#[test]
+fn batched_matches_scalar_multiply_theta()
+{
+    assert_batched_matches_scalar(FractalizeParameters::default().with_method(FractalMethod::MultiplyTheta));
+}
+
+#[test]
+fn batched_matches_scalar_other_angles()
+{
+    assert_batched_matches_scalar(FractalizeParameters::default().with_rot(-0.5).with_theta_offset(-PI * 0.75));
+}
+
+#[test]
+fn batched_renders_every_point()
+{
+    // 1000 u64s of branch decisions do not divide evenly into lanes.
+    let params = FractalizeParameters::default().with_max_points(64_000).with_seed(Some(0));
+
+    let scalar = render(params);
+    let batched = render(params.with_evaluation(Evaluation::Batched));
+
+    let total = |h: &Histogram| h.counts().iter().map(|&n| n as u64).sum::<u64>();
+    assert_eq!(total(&scalar), total(&batched));
+}
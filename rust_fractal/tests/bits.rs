@@ -0,0 +1,125 @@
+//! Pluggable branch-decision sources.
+
+mod common;
+
+use rust_fractal::{
+    BitSource, DefaultBits, Evaluation, Fractalize, FractalizeParameters, Histogram, LowDiscrepancyBits, PcgBits, XorshiftBits,
+};
+use common::density_distance;
+
+const SIZE: u32 = 64;
+const POINTS: u32 = 4_000_000;
+
+fn render<B: BitSource>(bits: B) -> Histogram
+{
+    let mut h = Histogram::new(SIZE, SIZE);
+    h.fractalize_with(FractalizeParameters::default().with_max_points(POINTS), bits).unwrap();
+    h
+}
+
+#[test]
+fn default_bits_match_fractalize()
+{
+    let params = FractalizeParameters::default().with_max_points(64_000).with_seed(Some(9));
+
+    let mut plain = Histogram::new(SIZE, SIZE);
+    plain.fractalize(params).unwrap();
+    let mut with = Histogram::new(SIZE, SIZE);
+    with.fractalize_with(params, DefaultBits::new(Some(9))).unwrap();
+
+    assert_eq!(plain.counts(), with.counts());
+}
+
+#[test]
+fn sources_converge_to_the_same_density()
+{
+    let reference = render(DefaultBits::new(Some(1)));
+    let noise = density_distance(reference.counts(), render(DefaultBits::new(Some(2))).counts());
+
+    let others = [
+        ("pcg", render(PcgBits::new(3))),
+        ("xorshift", render(XorshiftBits::new(4))),
+        ("low discrepancy", render(LowDiscrepancyBits::new(5))),
+    ];
+
+    for (name, h) in others
+    {
+        let distance = density_distance(reference.counts(), h.counts());
+        assert!(
+            distance < 0.02 && distance < 3.0 * noise.max(0.002),
+            "{name} density is {distance:.4} away from the default, sampling noise is {noise:.4}"
+        );
+    }
+}
+
+#[test]
+fn low_discrepancy_windows_are_stratified()
+{
+    // The first run is of order 8: 255 decisions.
+    let mut bits = LowDiscrepancyBits::new(1);
+    let words: Vec<u64> = (0..4).map(|_| bits.next_bits()).collect();
+    let decision = |i: usize| (words[i / 64] >> (i % 64)) & 1;
+
+    let mut seen = [false; 256];
+    for start in 0..255
+    {
+        let window = (0..8).fold(0, |w, j| (w << 1) | decision((start + j) % 255)) as usize;
+        assert!(!seen[window], "window {window:08b} occurs twice");
+        seen[window] = true;
+    }
+    assert!(!seen[0]);
+}
+
+#[test]
+fn low_discrepancy_error_is_no_worse_than_pcg()
+{
+    let reference = render(PcgBits::new(100)).counts().to_vec();
+
+    let points = 1 << 18;
+    let error = |bits: &mut dyn BitSource|
+    {
+        let mut h = Histogram::new(SIZE, SIZE);
+        h.fractalize_with(FractalizeParameters::default().with_max_points(points), bits).unwrap();
+        density_distance(&reference, h.counts())
+    };
+
+    let pcg = (0..4).map(|seed| error(&mut PcgBits::new(seed))).sum::<f64>() / 4.0;
+    let low_discrepancy = error(&mut LowDiscrepancyBits::new(1));
+    assert!(
+        low_discrepancy < 1.1 * pcg,
+        "low discrepancy error {low_discrepancy:.4} at {points} points, pcg {pcg:.4}"
+    );
+}
+
+#[test]
+fn seeded_sources_are_reproducible()
+{
+    let a = render(XorshiftBits::new(7));
+    let b = render(XorshiftBits::new(7));
+    assert_eq!(a.counts(), b.counts());
+}
+
+/// Counts how many u64s are drawn.
+struct Counting(u64);
+
+impl BitSource for Counting
+{
+    fn next_bits(&mut self) -> u64
+    {
+        self.0 += 1;
+        self.0.wrapping_mul(0x9e37_79b9_7f4a_7c15)
+    }
+}
+
+#[test]
+fn bits_are_drawn_once_per_64_points()
+{
+    // Spans several chunks, the last one partly filled.
+    for evaluation in [Evaluation::Scalar, Evaluation::Batched]
+    {
+        let mut counting = Counting(0);
+        let params = FractalizeParameters::default().with_max_points(64 * 10_003).with_evaluation(evaluation);
+        Histogram::new(SIZE, SIZE).fractalize_with(params, &mut counting).unwrap();
+        assert_eq!(counting.0, 10_003);
+    }
+}
@@ -0,0 +1,35 @@
+//! Helpers shared by the integration tests.
+// Each test binary compiles this module on its own and uses only part of it.
+#![allow(dead_code)]
+
+use std::path::PathBuf;
+
+use rust_fractal::Histogram;
+
+/// Total variation distance between the normalised densities of two sets of counts:
+/// half the summed absolute difference, 0 for identical and 1 for disjoint densities.
+pub fn density_distance(a: &[u32], b: &[u32]) -> f64
+{
+    let total = |h: &[u32]| h.iter().map(|&n| n as f64).sum::<f64>();
+    let (ta, tb) = (total(a), total(b));
+
+    a.iter()
+        .zip(b)
+        .map(|(&na, &nb)| (na as f64 / ta - nb as f64 / tb).abs())
+        .sum::<f64>() * 0.5
+}
+
+/// Number of hits in `h`.
+pub fn total(h: &Histogram) -> u64
+{
+    h.counts().iter().map(|&n| n as u64).sum()
+}
+
+/// A fresh, empty directory under the system temp dir.
+pub fn scratch_dir(name: &str) -> PathBuf
+{
+    let dir = std::env::temp_dir().join(format!("rust_fractal_{name}_{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
@@ -0,0 +1,62 @@
+//! Bad parameters and empty images are rejected before anything is drawn.
+
+mod common;
+
+use rust_fractal::{Evaluation, FractalError, Fractalize, FractalizeParameters, Histogram, MyColorImage};
+use common::total;
+
+fn params() -> FractalizeParameters
+{
+    FractalizeParameters::default().with_max_points(64_000).with_seed(Some(3))
+}
+
+#[test]
+fn nan_rot_is_rejected()
+{
+    for evaluation in [Evaluation::Scalar, Evaluation::Batched]
+    {
+        let mut h = Histogram::new(16, 16);
+        let result = h.fractalize(params().with_rot(f32::NAN).with_evaluation(evaluation));
+
+        assert!(matches!(result, Err(FractalError::NonFinite { parameter: "rot", value }) if value.is_nan()), "{result:?}");
+        assert_eq!(total(&h), 0);
+    }
+}
+
+#[test]
+fn zero_sized_images_are_rejected()
+{
+    let mut h = Histogram::new(0, 16);
+    assert!(matches!(h.fractalize(params()), Err(FractalError::ZeroSized { width: 0, height: 16 })));
+
+    let mut img = MyColorImage::new(16, 0);
+    assert!(matches!(img.fractalize(params()), Err(FractalError::ZeroSized { width: 16, height: 0 })));
+    assert!(img.as_raw().is_empty());
+}
+
+#[test]
+fn too_few_points_are_rejected()
+{
+    let mut img = MyColorImage::new(16, 16);
+    let result = img.fractalize(params().with_max_points(63));
+
+    assert!(matches!(result, Err(FractalError::TooFewPoints(63))), "{result:?}");
+    assert!(img.as_raw().iter().all(|&b| b == 0));
+
+    // One u64 of decisions is enough.
+    assert!(img.fractalize(params().with_max_points(64)).is_ok());
+}
+
+#[test]
+fn png_errors_are_only_io_errors_when_io_failed()
+{
+    let mut encoder = png::Encoder::new(Vec::new(), 4, 4);
+    encoder.set_color(png::ColorType::Rgba);
+    let mut writer = encoder.write_header().unwrap();
+    let wrong_length = writer.write_image_data(&[0; 3]).unwrap_err();
+
+    let io = png::EncodingError::IoError(std::io::ErrorKind::WriteZero.into());
+
+    assert!(matches!(FractalError::from(wrong_length), FractalError::Image(image::ImageError::Encoding(_))));
+    assert!(matches!(FractalError::from(io), FractalError::Io(e) if e.kind() == std::io::ErrorKind::WriteZero));
+}
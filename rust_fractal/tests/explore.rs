@@ -0,0 +1,118 @@
+//! Quality metrics and the random search.
+
+use rust_fractal::{
+    coverage, entropy, information_dimension, lyapunov, Accumulate, FractalizeParameters, Histogram, MapMetric, Metrics,
+    ParameterMap, RandomSearch,
+};
+
+const SIZE: u32 = 128;
+
+fn filled() -> Histogram
+{
+    let mut h = Histogram::new(SIZE, SIZE);
+    for r in 0..SIZE
+    {
+        for c in 0..SIZE
+        {
+            h.splat(c, r);
+        }
+    }
+    h
+}
+
+fn diagonal() -> Histogram
+{
+    let mut h = Histogram::new(SIZE, SIZE);
+    for i in 0..SIZE
+    {
+        h.splat(i, i);
+    }
+    h
+}
+
+#[test]
+fn metrics_of_simple_shapes()
+{
+    let empty = Histogram::new(SIZE, SIZE);
+    assert_eq!(coverage(&empty), 0.0);
+    assert_eq!(entropy(&empty), 0.0);
+    assert_eq!(information_dimension(&empty), 0.0);
+
+    let filled = filled();
+    assert_eq!(coverage(&filled), 1.0);
+    assert!((entropy(&filled) - 1.0).abs() < 1e-9);
+    assert!((information_dimension(&filled) - 2.0).abs() < 1e-6);
+
+    let line = diagonal();
+    assert!((information_dimension(&line) - 1.0).abs() < 0.05, "{}", information_dimension(&line));
+
+    let mut point = Histogram::new(SIZE, SIZE);
+    point.splat(3, 3);
+    assert_eq!(information_dimension(&point), 0.0);
+}
+
+#[test]
+fn lyapunov_is_reproducible_and_not_expanding()
+{
+    let p = FractalizeParameters::default().with_seed(Some(2));
+    let a = lyapunov(&p, 1 << 14);
+    assert_eq!(a, lyapunov(&p, 1 << 14));
+
+    // The maps shrink on average; the attractor is bounded.
+    assert!(a.is_finite() && a < 0.5, "{a}");
+}
+
+#[test]
+fn random_search_returns_the_best_first()
+{
+    let search = RandomSearch::default().with_candidates(12).with_top_k(4).with_preview_size(64).with_preview_points(50_000);
+    let found = search.run().unwrap();
+
+    assert_eq!(found.len(), 4);
+    assert!(found.windows(2).all(|w| w[0].score >= w[1].score));
+    for c in &found
+    {
+        assert_eq!(c.score, c.metrics.score());
+        assert_eq!((c.preview.width(), c.preview.height()), (64, 64));
+        assert!(c.params.rot.abs() <= std::f32::consts::PI && c.params.theta_offset.abs() <= std::f32::consts::PI);
+        assert_eq!(Metrics::measure(&c.params, 64).unwrap().0, c.metrics);
+    }
+
+    // Seeded, so a second search finds the same.
+    let again = search.run().unwrap();
+    assert!(found.iter().zip(&again).all(|(a, b)| a.params == b.params));
+}
+
+#[test]
+fn random_search_ranks_by_a_custom_score()
+{
+    let search = RandomSearch::default().with_candidates(8).with_top_k(8).with_preview_size(32).with_preview_points(20_000);
+    let found = search.run_with(|m| m.coverage).unwrap();
+
+    assert_eq!(found.len(), 8);
+    assert!(found.windows(2).all(|w| w[0].metrics.coverage >= w[1].metrics.coverage));
+}
+
+#[test]
+fn parameter_map_covers_the_angle_square()
+{
+    use std::f32::consts::PI;
+
+    let map = ParameterMap::default().with_size(4).with_preview_size(16).with_preview_points(4096);
+
+    let corner = map.params_at(0.0, 0.0);
+    assert_eq!((corner.rot, corner.theta_offset), (-PI, -PI));
+    let center = map.params_at(0.5, 0.5);
+    assert_eq!((center.rot, center.theta_offset), (0.0, 0.0));
+
+    let cell = map.cell(3, 0);
+    assert!((cell.rot - 0.75 * PI).abs() < 1e-6 && (cell.theta_offset + 0.75 * PI).abs() < 1e-6);
+
+    for metric in [MapMetric::Coverage, MapMetric::Entropy, MapMetric::Dimension, MapMetric::Lyapunov, MapMetric::Score]
+    {
+        let map = map.with_metric(metric);
+        let row = map.row(1).unwrap();
+        assert_eq!(row.len(), 4);
+        assert_eq!(row[2], map.value(2, 1).unwrap(), "{metric:?}");
+    }
+}
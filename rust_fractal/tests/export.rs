@@ -0,0 +1,104 @@
+//! Saving renders as image files.
+
+mod common;
+
+use std::io::Cursor;
+
+use rust_fractal::{next_free_path, parameter_stem, Export, ExportFormat, Fractalize, FractalizeParameters, Histogram, Symmetry, Tonemap};
+use common::scratch_dir;
+
+fn histogram() -> Histogram
+{
+    let mut h = Histogram::new(96, 64);
+    h.fractalize(FractalizeParameters::default().with_max_points(200_000).with_seed(Some(1))).unwrap();
+    h
+}
+
+#[test]
+fn every_format_decodes_at_the_right_size()
+{
+    let h = histogram();
+    let tonemap = Tonemap::default().with_exposure(4.0);
+
+    for format in ExportFormat::ALL
+    {
+        let mut bytes = Cursor::new(Vec::new());
+        Export::default().with_format(format).write(&h, &tonemap, &mut bytes).unwrap();
+
+        let decoded = image::load_from_memory(bytes.get_ref()).unwrap();
+        assert_eq!((decoded.width(), decoded.height()), (96, 64), "{format:?}");
+
+        let expected = match format
+        {
+            ExportFormat::Png | ExportFormat::Tiff => image::ColorType::Rgba8,
+            ExportFormat::Jpeg => image::ColorType::Rgb8,
+            ExportFormat::Png16 => image::ColorType::Rgb16,
+        };
+        assert_eq!(decoded.color(), expected, "{format:?}");
+    }
+}
+
+#[test]
+fn sixteen_bit_export_matches_the_8_bit_tonemap()
+{
+    let h = histogram();
+    let tonemap = Tonemap::default().with_exposure(8.0).with_low([10, 20, 30]).with_high([250, 200, 150]);
+
+    let eight = tonemap.apply(&h);
+    let sixteen = tonemap.apply_16(&h);
+    for (a, b) in eight.pixels().zip(sixteen.pixels())
+    {
+        for c in 0..3
+        {
+            assert!((a.0[c] as f32 - b.0[c] as f32 / 257.0).abs() <= 0.5, "{a:?} vs {b:?}");
+        }
+    }
+}
+
+#[test]
+fn free_paths_count_up()
+{
+    let dir = scratch_dir("free_paths");
+    let stem = dir.join("render");
+
+    let first = next_free_path(&stem, "png");
+    assert_eq!(first, dir.join("render.png"));
+
+    let h = histogram();
+    Export::default().save(&h, &Tonemap::default(), &first).unwrap();
+    assert!(first.exists());
+    assert!(!dir.join("render.png.part").exists());
+
+    assert_eq!(next_free_path(&stem, "png"), dir.join("render_2.png"));
+    std::fs::write(dir.join("render_2.png"), b"").unwrap();
+    assert_eq!(next_free_path(&stem, "png"), dir.join("render_3.png"));
+    // Other extensions are counted separately.
+    assert_eq!(next_free_path(&stem, "jpg"), dir.join("render.jpg"));
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn failed_saves_leave_nothing_behind()
+{
+    let dir = scratch_dir("failed_save");
+    let path = dir.join("missing").join("render.png");
+
+    assert!(Export::default().save(&histogram(), &Tonemap::default(), &path).is_err());
+    assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 0);
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn parameter_stems_are_plain_file_names()
+{
+    let p = FractalizeParameters::default()
+        .with_rot(0.5)
+        .with_theta_offset(-1.25)
+        .with_symmetry(Symmetry::new(6, true));
+    assert_eq!(parameter_stem(&p), "fractal_rot+0.5000_theta-1.2500_sym6m");
+
+    let plain = parameter_stem(&FractalizeParameters::default());
+    assert!(plain.chars().all(|c| c.is_ascii_alphanumeric() || "_+-.".contains(c)), "{plain}");
+}
@@ -0,0 +1,64 @@
+//! Error bound of `fast_sincos` and its effect on rendered densities.
+
+mod common;
+
+use std::f32::consts::PI;
+
+use rust_fractal::{fast_sincos, FractalMethod, Fractalize, FractalizeParameters, Histogram, TrigMode};
+use common::density_distance;
+
+/// The documented absolute error bound for `|theta| <= 4 * PI`.
+const SINCOS_BOUND: f64 = 5e-7;
+
+/// Largest total variation distance allowed between exact and fast densities.
+const DENSITY_TOLERANCE: f64 = 0.02;
+
+#[test]
+fn fast_sincos_is_within_bound()
+{
+    let steps = 1_000_000;
+    let worst = (-steps..=steps)
+        .map(|i| i as f32 / steps as f32 * 4.0 * PI)
+        .map(|theta|
+        {
+            let (sin, cos) = fast_sincos(theta);
+            let (exact_sin, exact_cos) = (theta as f64).sin_cos();
+            (sin as f64 - exact_sin).abs().max((cos as f64 - exact_cos).abs())
+        })
+        .fold(0.0, f64::max);
+
+    assert!(worst < SINCOS_BOUND, "fast_sincos is off by {worst:e}");
+}
+
+fn assert_fast_matches_exact(params: FractalizeParameters)
+{
+    let params = params.with_max_points(4_000_000).with_seed(Some(11));
+
+    let render = |trig: TrigMode|
+    {
+        let mut h = Histogram::new(64, 64);
+        h.fractalize(params.with_trig(trig)).unwrap();
+        h
+    };
+
+    let distance = density_distance(render(TrigMode::Exact).counts(), render(TrigMode::Fast).counts());
+    assert!(distance < DENSITY_TOLERANCE, "fast trig density is {distance:.4} away from exact");
+}
+
+#[test]
+fn fast_trig_matches_exact_default()
+{
+    assert_fast_matches_exact(FractalizeParameters::default());
+}
+
+#[test]
+fn fast_trig_matches_exact_multiply_theta()
+{
+    assert_fast_matches_exact(FractalizeParameters::default().with_method(FractalMethod::MultiplyTheta));
+}
+
+#[test]
+fn fast_trig_matches_exact_other_angles()
+{
+    assert_fast_matches_exact(FractalizeParameters::default().with_rot(2.5).with_theta_offset(-PI * 0.3));
+}
@@ -0,0 +1,69 @@
+//! Automatic framing of the attractor.
+
+mod common;
+
+use rust_fractal::{FractalError, Fractalize, FractalizeParameters, FractalMethod, Framing, Histogram, Viewport};
+use common::total;
+
+fn hits(params: FractalizeParameters) -> u64
+{
+    let mut h = Histogram::new(256, 256);
+    h.fractalize(params).unwrap();
+    total(&h)
+}
+
+fn cases() -> Vec<FractalizeParameters>
+{
+    let p = FractalizeParameters::default().with_max_points(500_000).with_seed(Some(4));
+    vec![
+        p,
+        p.with_theta_offset(-1.0),
+        p.with_rot(0.3).with_theta_offset(2.0),
+        p.with_method(FractalMethod::MultiplyTheta).with_theta_offset(0.2),
+        p.with_init_x_y((3.0, -2.5)),
+    ]
+}
+
+#[test]
+fn fitted_viewport_keeps_almost_every_hit()
+{
+    for p in cases()
+    {
+        let viewport = Framing::default().fit(&p).unwrap();
+
+        // Same seed, so the same orbit; only the mapping to pixels differs.
+        let everything = hits(p.with_viewport(Viewport::centered(0.0, 0.0, 10.0)));
+        let framed = hits(p.with_viewport(viewport));
+
+        assert!(
+            framed as f64 >= 0.99 * everything as f64,
+            "{viewport:?} keeps {framed} of {everything} hits for {p:?}"
+        );
+        assert!(viewport.width() < 2.5 && viewport.height() < 2.5, "{viewport:?} is loose for {p:?}");
+    }
+}
+
+#[test]
+fn fitted_viewport_has_the_requested_aspect()
+{
+    for aspect in [0.5, 1.0, 16.0 / 9.0]
+    {
+        let viewport = Framing::default().with_aspect(aspect).fit(&cases()[0]).unwrap();
+        let actual = viewport.width() / viewport.height();
+        assert!((actual - aspect).abs() < 1e-9, "asked for {aspect}, got {actual}");
+    }
+}
+
+#[test]
+fn invalid_framing_is_rejected()
+{
+    let p = FractalizeParameters::default();
+    for framing in [
+        Framing::default().with_trim(0.5),
+        Framing::default().with_margin(-0.1),
+        Framing::default().with_aspect(0.0),
+    ]
+    {
+        assert!(matches!(framing.fit(&p), Err(FractalError::InvalidFraming(_))), "{framing:?}");
+    }
+}
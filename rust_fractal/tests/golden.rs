@@ -0,0 +1,177 @@
+//! Golden-image regression tests.
+//!
+//! Small seeded renders are hashed and compared against `tests/golden/hashes.txt`.
+//! After an intentional change to the output, refresh the references with
+//!
+//! ```sh
+//! UPDATE_GOLDEN=1 cargo test --test golden
+//! ```
+//!
+//! and check the diff of `hashes.txt` in review. On a mismatch the offending
+//! renders are written to cargo's test tmp dir for a visual comparison.
+//!
+//! The branch decisions come from [`PcgBits`], whose output is fixed by its
+//! algorithm, so a `rand` update does not change the references. The orbit
+//! still uses the platform's `f32::sin` and `f32::cos`, which are not bit-exact
+//! across platforms and libm versions: the references are only valid for the
+//! platform and toolchain they were generated on (x86_64 Linux). Elsewhere,
+//! generate them locally before making the change under test.
+
+use std::collections::BTreeMap;
+use std::f32::consts::PI;
+
+use rust_fractal::{Accumulate, FractalMethod, Fractalize, FractalizeParameters, Histogram, MyColorImage, PcgBits};
+
+const HASHES: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/golden/hashes.txt");
+
+struct Case
+{
+    name: &'static str,
+    width: u32,
+    height: u32,
+    params: FractalizeParameters,
+}
+
+fn cases() -> Vec<Case>
+{
+    let base = FractalizeParameters::default()
+        .with_max_points(200_000)
+        .with_seed(Some(0x5eed));
+
+    vec![
+        Case { name: "default", width: 128, height: 128, params: base },
+        Case { name: "multiply_theta", width: 128, height: 128, params: base.with_method(FractalMethod::MultiplyTheta) },
+        Case { name: "negative_angles", width: 128, height: 128, params: base.with_rot(-0.5).with_theta_offset(-PI * 0.75) },
+        Case { name: "multiply_theta_small_offset", width: 128, height: 128, params: base.with_method(FractalMethod::MultiplyTheta).with_theta_offset(0.3) },
+        Case { name: "off_center_start", width: 128, height: 128, params: base.with_init_x_y((0.7, -0.2)) },
+        Case { name: "non_square", width: 160, height: 90, params: base },
+        Case { name: "other_seed", width: 128, height: 128, params: base.with_seed(Some(7)) },
+        Case { name: "many_points", width: 64, height: 64, params: base.with_max_points(2_000_000) },
+    ]
+}
+
+/// FNV-1a, which unlike `DefaultHasher` is stable across Rust releases.
+fn fnv1a(bytes: impl IntoIterator<Item = u8>) -> u64
+{
+    bytes.into_iter().fold(0xcbf2_9ce4_8422_2325, |h, b| (h ^ b as u64).wrapping_mul(0x0100_0000_01b3))
+}
+
+fn render<G: Accumulate>(mut grid: G, case: &Case) -> G
+{
+    let seed = case.params.seed.expect("golden cases are seeded");
+    grid.fractalize_with(case.params, PcgBits::new(seed)).expect("golden case parameters are valid");
+    grid
+}
+
+/// Hashes of every case, for both accumulators.
+fn actual_hashes() -> BTreeMap<String, u64>
+{
+    let mut hashes = BTreeMap::new();
+    for case in cases()
+    {
+        let img = render(MyColorImage::new(case.width, case.height), &case);
+        hashes.insert(format!("{}.rgba8", case.name), fnv1a(img.as_raw().iter().copied()));
+
+        let hist = render(Histogram::new(case.width, case.height), &case);
+        hashes.insert(format!("{}.hist32", case.name), fnv1a(hist.counts().iter().flat_map(|n| n.to_le_bytes())));
+    }
+
+    hashes
+}
+
+fn read_references() -> BTreeMap<String, u64>
+{
+    let text = std::fs::read_to_string(HASHES).unwrap_or_default();
+    text.lines()
+        .filter(|l| !l.trim().is_empty() && !l.starts_with('#'))
+        .map(|l|
+        {
+            let (name, hash) = l.split_once(' ').expect("reference lines are `name hash`");
+            (name.to_string(), u64::from_str_radix(hash.trim(), 16).expect("reference hash is hex"))
+        })
+        .collect()
+}
+
+fn write_references(hashes: &BTreeMap<String, u64>)
+{
+    let mut text = String::from("# Generated by `UPDATE_GOLDEN=1 cargo test --test golden`.\n");
+    for (name, hash) in hashes
+    {
+        text += &format!("{name} {hash:016x}\n");
+    }
+
+    std::fs::create_dir_all(std::path::Path::new(HASHES).parent().unwrap()).unwrap();
+    std::fs::write(HASHES, text).unwrap();
+}
+
+/// Writes the renders of mismatching cases next to each other for inspection.
+fn dump_mismatches(mismatched: &[String])
+{
+    let dir = std::path::Path::new(env!("CARGO_TARGET_TMPDIR")).join("golden");
+    std::fs::create_dir_all(&dir).unwrap();
+
+    for case in cases().iter().filter(|c| mismatched.iter().any(|m| m.starts_with(c.name)))
+    {
+        let mut img = render(MyColorImage::new(case.width, case.height), case);
+        img.pixels_mut().for_each(|p| p[3] = 0xff);
+        img.save(dir.join(format!("{}.png", case.name))).unwrap();
+    }
+
+    eprintln!("mismatching renders written to {}", dir.display());
+}
+
+#[test]
+fn golden_images()
+{
+    let actual = actual_hashes();
+
+    if std::env::var_os("UPDATE_GOLDEN").is_some()
+    {
+        write_references(&actual);
+        return;
+    }
+
+    let expected = read_references();
+    let mismatched: Vec<String> = actual.iter()
+        .filter(|(name, hash)| expected.get(*name) != Some(hash))
+        .map(|(name, _)| name.clone())
+        .collect();
+    let stale: Vec<&String> = expected.keys().filter(|name| !actual.contains_key(*name)).collect();
+
+    if !mismatched.is_empty()
+    {
+        dump_mismatches(&mismatched);
+    }
+
+    assert!(
+        mismatched.is_empty() && stale.is_empty(),
+        "golden renders changed: {mismatched:?}, references without a case: {stale:?}. \
+        If this is intended, refresh them with `UPDATE_GOLDEN=1 cargo test --test golden`."
+    );
+}
+
+#[test]
+fn seeded_renders_are_reproducible()
+{
+    let case = &cases()[0];
+
+    let a = render(MyColorImage::new(case.width, case.height), case);
+    let b = render(MyColorImage::new(case.width, case.height), case);
+
+    assert_eq!(a, b);
+}
+
+#[test]
+fn histogram_matches_color_image_below_saturation()
+{
+    for case in cases()
+    {
+        let img = render(MyColorImage::new(case.width, case.height), &case);
+        let hist = render(Histogram::new(case.width, case.height), &case);
+
+        let agree = img.pixels()
+            .zip(hist.counts())
+            .all(|(p, &n)| p[0] as u32 == n.min(255) && p[0] == p[1] && p[1] == p[2] && p[3] == 0);
+        assert!(agree, "{} differs between accumulators", case.name);
+    }
+}
@@ -0,0 +1,129 @@
+//! The wide histogram and its export formats.
+
+use std::io::Cursor;
+
+use rust_fractal::{Accumulate, Histogram, MyColorImage, RawHistogramError};
+
+/// A 5x3 histogram with pixel `(c, 0)` hit `counts[c]` times.
+fn histogram(counts: &[u32]) -> Histogram
+{
+    let mut h = Histogram::new(5, 3);
+    for (c, &n) in counts.iter().enumerate()
+    {
+        for _ in 0..n
+        {
+            h.splat(c as u32, 0);
+        }
+    }
+    h
+}
+
+fn u32_at(bytes: &[u8], at: usize) -> u32
+{
+    u32::from_le_bytes(bytes[at..at + 4].try_into().unwrap())
+}
+
+#[test]
+fn raw_header_layout()
+{
+    let h = histogram(&[1, 300, 0, 7]);
+    let mut bytes = Vec::new();
+    h.write_raw(&mut bytes).unwrap();
+
+    assert_eq!(bytes[..8], Histogram::RAW_MAGIC);
+    assert_eq!(u32_at(&bytes, 8), Histogram::RAW_VERSION);
+    assert_eq!((u32_at(&bytes, 12), u32_at(&bytes, 16)), (5, 3));
+    assert_eq!(bytes.len(), 20 + 5 * 3 * 4);
+
+    let counts: Vec<u32> = (0..15).map(|i| u32_at(&bytes, 20 + 4 * i)).collect();
+    assert_eq!(counts, h.counts());
+}
+
+#[test]
+fn npy_header_is_padded_and_row_major()
+{
+    let h = histogram(&[1, 300, 0, 7]);
+    let mut bytes = Vec::new();
+    h.write_npy(&mut bytes).unwrap();
+
+    assert_eq!(bytes[..8], *b"\x93NUMPY\x01\x00");
+    let header_len = u16::from_le_bytes([bytes[8], bytes[9]]) as usize;
+    let data_start = 10 + header_len;
+    assert_eq!(data_start % 64, 0);
+
+    let header = std::str::from_utf8(&bytes[10..data_start]).unwrap();
+    assert!(header.ends_with('\n'), "{header:?}");
+    assert!(header.starts_with("{'descr': '<u4', 'fortran_order': False, 'shape': (3, 5), }"), "{header:?}");
+
+    assert_eq!(bytes.len(), data_start + 15 * 4);
+    assert_eq!(u32_at(&bytes, data_start + 4), 300);
+}
+
+#[test]
+fn sixteen_bit_exports_keep_counts_past_255()
+{
+    let h = histogram(&[300, 70_000]);
+
+    let mut img = MyColorImage::new(5, 3);
+    for _ in 0..300
+    {
+        img.splat(0, 0);
+    }
+    assert_eq!(img.get_pixel(0, 0).0[..3], [255; 3]);
+
+    let mut png = Cursor::new(Vec::new());
+    h.to_luma16().write_to(&mut png, image::ImageFormat::Png).unwrap();
+    let luma = image::load_from_memory(png.get_ref()).unwrap().into_luma16();
+    assert_eq!((luma.get_pixel(0, 0).0, luma.get_pixel(1, 0).0), ([300], [u16::MAX]));
+
+    let mut png = Cursor::new(Vec::new());
+    h.to_rgb16().write_to(&mut png, image::ImageFormat::Png).unwrap();
+    let rgb = image::load_from_memory(png.get_ref()).unwrap().into_rgb16();
+    assert_eq!((rgb.get_pixel(0, 0).0, rgb.get_pixel(1, 0).0), ([300; 3], [u16::MAX; 3]));
+}
+
+fn raw(h: &Histogram) -> Vec<u8>
+{
+    let mut bytes = Vec::new();
+    h.write_raw(&mut bytes).unwrap();
+    bytes
+}
+
+#[test]
+fn raw_round_trip()
+{
+    let h = histogram(&[1, 300, 0, 70_000]);
+    let back = Histogram::read_raw(raw(&h).as_slice()).unwrap();
+
+    assert_eq!((back.width(), back.height()), (5, 3));
+    assert_eq!(back.counts(), h.counts());
+}
+
+#[test]
+fn raw_errors()
+{
+    let good = raw(&histogram(&[1, 2, 3]));
+    let read = |bytes: &[u8]| Histogram::read_raw(bytes).map(|_| ()).unwrap_err();
+
+    let mut bad_magic = good.clone();
+    bad_magic[0] = b'X';
+    assert!(matches!(read(&bad_magic), RawHistogramError::BadMagic));
+
+    let mut newer = good.clone();
+    newer[8..12].copy_from_slice(&2u32.to_le_bytes());
+    assert!(matches!(read(&newer), RawHistogramError::UnsupportedVersion(2)));
+
+    let mut zero_width = good.clone();
+    zero_width[12..16].copy_from_slice(&0u32.to_le_bytes());
+    assert!(matches!(read(&zero_width), RawHistogramError::ZeroSized));
+
+    let truncated = &good[..good.len() - 1];
+    assert!(matches!(read(truncated), RawHistogramError::SizeMismatch { width: 5, height: 3, actual_bytes: 59 }));
+
+    let mut extra = good.clone();
+    extra.extend([0; 4]);
+    assert!(matches!(read(&extra), RawHistogramError::SizeMismatch { width: 5, height: 3, actual_bytes: 64 }));
+
+    // Ends inside the height.
+    assert!(matches!(read(&good[..18]), RawHistogramError::Io(e) if e.kind() == std::io::ErrorKind::UnexpectedEof));
+}
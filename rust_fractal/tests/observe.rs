@@ -0,0 +1,60 @@
+//! Observing and cancelling a render.
+
+mod common;
+
+use std::ops::ControlFlow;
+
+use rust_fractal::{DefaultBits, Evaluation, FractalError, Fractalize, FractalizeParameters, Histogram};
+use common::total;
+
+#[test]
+fn progress_counts_up_to_every_point()
+{
+    for evaluation in [Evaluation::Scalar, Evaluation::Batched]
+    {
+        let params = FractalizeParameters::default().with_max_points(3_000_000).with_seed(Some(1)).with_evaluation(evaluation);
+
+        let mut seen = Vec::new();
+        let mut h = Histogram::new(64, 64);
+        h.fractalize_observed(params, DefaultBits::new(params.seed), |grid, points|
+        {
+            seen.push((points, total(grid)));
+            ControlFlow::Continue(())
+        }).unwrap();
+
+        assert!(seen.len() > 1, "{evaluation:?}: observed only {seen:?}");
+        assert!(seen.windows(2).all(|w| w[0].0 < w[1].0 && w[0].1 <= w[1].1), "{evaluation:?}: {seen:?}");
+        assert_eq!(seen.last().unwrap(), &(3_000_000 / 64 * 64, total(&h)));
+    }
+}
+
+#[test]
+fn observed_render_matches_plain_render()
+{
+    let params = FractalizeParameters::default().with_max_points(1_000_000).with_seed(Some(2));
+
+    let mut plain = Histogram::new(64, 64);
+    plain.fractalize(params).unwrap();
+    let mut observed = Histogram::new(64, 64);
+    observed.fractalize_observed(params, DefaultBits::new(params.seed), |_, _| ControlFlow::Continue(())).unwrap();
+
+    assert_eq!(plain.counts(), observed.counts());
+}
+
+#[test]
+fn breaking_cancels_and_keeps_what_was_drawn()
+{
+    let params = FractalizeParameters::default().with_max_points(50_000_000).with_seed(Some(3));
+
+    let mut calls = 0;
+    let mut h = Histogram::new(64, 64);
+    let result = h.fractalize_observed(params, DefaultBits::new(params.seed), |_, _|
+    {
+        calls += 1;
+        if calls == 2 { ControlFlow::Break(()) } else { ControlFlow::Continue(()) }
+    });
+
+    assert!(matches!(result, Err(FractalError::Cancelled)));
+    assert_eq!(calls, 2);
+    assert!(total(&h) > 0 && total(&h) < 2 * 50_000_000 / 10);
+}
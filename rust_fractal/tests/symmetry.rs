@@ -0,0 +1,85 @@
+//! Rotational and mirror symmetry.
+
+mod common;
+
+use rust_fractal::{
+    Evaluation, FractalError, Fractalize, FractalizeParameters, Histogram, Precision, Symmetry,
+};
+use common::{density_distance, total};
+
+const SIZE: u32 = 128;
+
+fn render(params: FractalizeParameters) -> Histogram
+{
+    let mut h = Histogram::new(SIZE, SIZE);
+    h.fractalize(params.with_max_points(1_000_000).with_seed(Some(8))).unwrap();
+    h
+}
+
+/// The counts of `h` with every pixel moved to `map(c, r)`.
+fn remap(h: &Histogram, map: impl Fn(u32, u32) -> (u32, u32)) -> Vec<u32>
+{
+    let mut out = vec![0; h.counts().len()];
+    for r in 0..SIZE
+    {
+        for c in 0..SIZE
+        {
+            let (mc, mr) = map(c, r);
+            out[(mr * SIZE + mc) as usize] = h.get(c, r).unwrap();
+        }
+    }
+    out
+}
+
+#[test]
+fn no_symmetry_is_the_default()
+{
+    let params = FractalizeParameters::default();
+    assert_eq!(render(params).counts(), render(params.with_symmetry(Symmetry::new(1, false))).counts());
+}
+
+#[test]
+fn mirror_is_symmetric_across_the_x_axis()
+{
+    for evaluation in [Evaluation::Scalar, Evaluation::Batched]
+    {
+        let params = FractalizeParameters::default().with_evaluation(evaluation);
+        let plain = render(params);
+        let mirrored = render(params.with_symmetry(Symmetry::new(1, true)));
+
+        // The default viewport is symmetric about the x axis, so no image is lost.
+        assert_eq!(total(&mirrored), 2 * total(&plain));
+
+        let flipped = remap(&mirrored, |c, r| (c, SIZE - 1 - r));
+        let distance = density_distance(mirrored.counts(), &flipped);
+        assert!(distance < 0.01, "{evaluation:?}: mirror image is {distance:.4} away");
+        assert!(density_distance(plain.counts(), &remap(&plain, |c, r| (c, SIZE - 1 - r))) > 0.1);
+    }
+}
+
+#[test]
+fn fourfold_is_symmetric_under_quarter_turns()
+{
+    for precision in [Precision::F32, Precision::F64]
+    {
+        let h = render(FractalizeParameters::default().with_precision(precision).with_symmetry(Symmetry::new(4, false)));
+
+        // (x, y) -> (-y, x) on the grid.
+        let turned = remap(&h, |c, r| (SIZE - 1 - r, c));
+        let distance = density_distance(h.counts(), &turned);
+        assert!(distance < 0.01, "{precision:?}: quarter turn is {distance:.4} away");
+    }
+}
+
+#[test]
+fn orders_out_of_range_are_rejected()
+{
+    for order in [0, Symmetry::MAX_ORDER + 1, u32::MAX]
+    {
+        let params = FractalizeParameters::default().with_symmetry(Symmetry::new(order, true));
+        assert!(matches!(params.validate(), Err(FractalError::InvalidSymmetry(_))), "{order}");
+    }
+
+    let params = FractalizeParameters::default().with_symmetry(Symmetry::new(Symmetry::MAX_ORDER, true));
+    assert!(params.validate().is_ok());
+}
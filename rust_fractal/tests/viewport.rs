@@ -0,0 +1,97 @@
+//! Viewport mapping and the f64 orbit.
+
+mod common;
+
+use rust_fractal::{FractalError, Fractalize, FractalizeParameters, Histogram, Precision, Viewport};
+use common::{density_distance, total};
+
+fn render(size: u32, params: FractalizeParameters) -> Histogram
+{
+    let mut h = Histogram::new(size, size);
+    h.fractalize(params).unwrap();
+    h
+}
+
+fn params() -> FractalizeParameters
+{
+    FractalizeParameters::default().with_max_points(2_000_000).with_seed(Some(5))
+}
+
+#[test]
+fn quadrant_viewport_matches_crop_of_full_view()
+{
+    let full = render(128, params());
+    let quadrant = render(64, params().with_viewport(Viewport { x_min: 0.0, x_max: 1.0, y_min: 0.0, y_max: 1.0 }));
+
+    // Same orbit, so apart from rounding at pixel edges the hits are identical.
+    let mut moved = 0;
+    for r in 0..64
+    {
+        for c in 0..64
+        {
+            moved += full.get(c + 64, r + 64).unwrap().abs_diff(quadrant.get(c, r).unwrap()) as u64;
+        }
+    }
+
+    assert!(moved * 1000 < total(&quadrant), "{moved} of {} hits moved", total(&quadrant));
+}
+
+#[test]
+fn points_outside_viewport_are_dropped()
+{
+    let far_away = render(32, params().with_viewport(Viewport::centered(5.0, 5.0, 0.5)));
+    assert_eq!(total(&far_away), 0);
+}
+
+#[test]
+fn invalid_viewports_are_rejected()
+{
+    let inverted = Viewport { x_min: 1.0, x_max: -1.0, y_min: -1.0, y_max: 1.0 };
+    let empty = Viewport::centered(0.0, 0.0, 0.0);
+    let nan = Viewport::centered(f64::NAN, 0.0, 1.0);
+
+    for viewport in [inverted, empty, nan]
+    {
+        let mut h = Histogram::new(8, 8);
+        let result = h.fractalize(params().with_viewport(viewport));
+        assert!(matches!(result, Err(FractalError::InvalidViewport(_))), "{viewport:?} was accepted");
+    }
+}
+
+#[test]
+fn f64_matches_f32_density()
+{
+    let params = params().with_max_points(4_000_000);
+
+    let f32_render = render(64, params);
+    let f32_other_seed = render(64, params.with_seed(Some(6)));
+    let f64_render = render(64, params.with_precision(Precision::F64));
+
+    let noise = density_distance(f32_render.counts(), f32_other_seed.counts());
+    let distance = density_distance(f32_render.counts(), f64_render.counts());
+
+    assert!(distance < 3.0 * noise.max(0.002), "f64 density is {distance:.4} away from f32, noise is {noise:.4}");
+}
+
+#[test]
+fn f64_stays_sharp_at_deep_zoom()
+{
+    // With theta offset 2 the polar map has an attracting fixed point here; a tiny rotation
+    // spreads the orbit into a cloud about 1e-6 across around it.
+    let (x, y) = (-0.297_857_286_181_578, 0.185_828_239_809_293);
+    let params = params()
+        .with_max_points(1_000_000)
+        .with_rot(1e-6)
+        .with_theta_offset(2.0)
+        .with_init_x_y((x as f32, y as f32))
+        .with_viewport(Viewport::centered(x, y, 5e-7));
+
+    let occupied = |h: &Histogram| h.counts().iter().filter(|&&c| c > 0).count();
+    let f32_render = render(128, params);
+    let f64_render = render(128, params.with_precision(Precision::F64));
+
+    // Cells are 8e-9 wide, a quarter of an f32 ulp at x, so f32 can only reach a few of them.
+    assert!(total(&f32_render) > 500_000 && total(&f64_render) > 500_000);
+    assert!(occupied(&f64_render) > 5 * occupied(&f32_render),
+        "f64 filled {} cells, f32 {}", occupied(&f64_render), occupied(&f32_render));
+}
@@ -1,15 +1,19 @@
 use std::f32::consts::PI;
+use std::ops::ControlFlow;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::{Duration, Instant};
 
 use bevy::prelude::*;
 use bevy::tasks::{block_on, poll_once, AsyncComputeTaskPool, Task};
-use bevy::sprite::Anchor;
 use bevy::render::render_resource::{Extent3d, TextureFormat};
 use bevy::asset::RenderAssetUsages;
 
 use bevy_egui::{EguiContextPass, EguiContexts};
 use bevy_egui::egui;
 
-use rust_fractal::{Fractalize, FractalizeParameters, FractalMethod, MyColorImage};
+use rust_fractal::{next_free_path, parameter_stem, Candidate, Curve, DefaultBits, Export, ExportFormat, FractalError, Fractalize, FractalizeParameters, FractalMethod, Framing, Histogram, MapMetric, MyColorImage, ParameterMap, Precision, RandomSearch, Symmetry, Tonemap, Viewport};
 
 pub struct FractalPlugin;
 
@@ -19,39 +23,94 @@ impl Plugin for FractalPlugin
     {
         app
         .add_event::<FractalEvent>()
-        .add_systems(EguiContextPass, fractal_gui)
+        .add_event::<SaveFinished>()
+        .init_resource::<FractalStatus>()
+        .init_resource::<RenderQueue>()
+        .init_resource::<AutoPreview>()
+        .init_resource::<SaveDialog>()
+        .init_resource::<Explore>()
+        .init_resource::<MapView>()
+        .add_systems(EguiContextPass, (fractal_gui, explore_gui, map_gui))
         .add_systems(Startup, fractal_setup)
-        .add_systems(Update, (fractal_event, handle_compute_fractal))
+        .add_systems(Update, (auto_preview, fractal_event, handle_compute_fractal, handle_compute_save, save_finished, handle_compute_explore, handle_compute_map_row).chain())
         ;
     }
 }
 
+/// Width of the sprite in world units, whatever the resolution, so the camera zoom keeps its meaning.
+const WORLD_SIZE: f32 = 4096.0;
+const DEFAULT_RESOLUTION: UVec2 = UVec2::splat(4096);
+/// Offered in the settings window, next to a custom width and height.
+const RESOLUTION_PRESETS: [u32; 4] = [1024, 2048, 4096, 8192];
+/// Limits of the custom width and height.
+const RESOLUTION_RANGE: std::ops::RangeInclusive<u32> = 16..=16384;
+
 fn fractal_setup(
     mut commands: Commands,
+    mut fractal_ew: EventWriter<FractalEvent>,
 )
 {
-    let fractal = MyColorImage::new(4096, 4096);
+    let resolution = DEFAULT_RESOLUTION;
+    let fractal = MyColorImage::new(resolution.x, resolution.y);
+    let histogram = Arc::new(Histogram::new(resolution.x, resolution.y));
     let params = 
         FractalizeParameters::default()
         .with_max_points(25_000_000);
+    let tonemap = Tonemap::default();
+
+    // The image is shown by tile sprites under this one, made on the first display.
+    let parent = commands.spawn((
+        FractalSprite,
+        Transform::from_translation([0.0, 0.0, -1.0].into()),
+        Visibility::default(),
+    )).id();
+    commands.insert_resource(FractalTiles { parent, tiles: Vec::new() });
+    fractal_ew.write(FractalEvent::Display);
 
     commands.insert_resource(Fractal {
         fractal,
+        histogram,
+        preview: false,
+        resolution,
         params,
+        tonemap,
     });
     commands.insert_resource(FractalSettingsMenu {
         fractal_method: FractalMethod::default(),
         f_theta_offset: params.theta_offset,
         f_rot: params.rot,
         u_num_points: params.max_points,
+        precision: params.precision,
+        d_view_x: 0.0,
+        d_view_y: 0.0,
+        d_view_zoom: 1.0,
+        symmetry: params.symmetry,
+        resolution,
+        tonemap,
+        s_histogram_path: "my_histogram.rfhist".to_string(),
     });
 }
 
 #[derive(Event)]
 enum FractalEvent
 {
+    /// Start a render, cancelling the one running.
     Render,
+    /// Start a quick low resolution render, see [`AutoPreview`].
+    Preview,
+    Cancel,
     Settings(FractalizeParameters),
+    /// Re-tonemap the current histogram without rendering again.
+    Tonemap(Tonemap),
+    LoadHistogram(PathBuf),
+    /// Save the current histogram in the background, see [`ComputeSave`].
+    Save
+    {
+        path: PathBuf,
+        kind: SaveKind,
+    },
+    /// Reallocate the image at a new width and height.
+    Resolution(UVec2),
     Display,
 }
 
@@ -62,63 +121,534 @@ struct FractalSettingsMenu
     f_theta_offset: f32,
     f_rot: f32,
     u_num_points: u32,
+    precision: Precision,
+    d_view_x: f64,
+    d_view_y: f64,
+    /// Magnification of the rendered region; 1 shows [-1, 1] across the width.
+    d_view_zoom: f64,
+    symmetry: Symmetry,
+    /// Width and height picked in the gui, applied when a preset is picked or an edit ends.
+    resolution: UVec2,
+    tonemap: Tonemap,
+    s_histogram_path: String,
+}
+
+/// The most recent failure, shown in the settings window until dismissed.
+#[derive(Resource, Default)]
+struct FractalStatus
+{
+    error: Option<String>,
+}
+
+impl FractalStatus
+{
+    fn report(&mut self, what: &str, e: impl std::fmt::Display)
+    {
+        println!("{what}: {e}");
+        self.error = Some(format!("{what}: {e}"));
+    }
+}
+
+/// How the save panel picks a file name.
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+enum SaveNaming
+{
+    /// The name as typed, asking before replacing an existing file.
+    #[default]
+    AsTyped,
+    /// The name as typed, with `_2`, `_3`, ... added until it is free.
+    Increment,
+    /// A name made from the render settings, numbered like `Increment`.
+    Parameters,
+}
+
+/// State of the save panel in the settings window.
+#[derive(Resource)]
+struct SaveDialog
+{
+    /// Path without the extension, which comes from the format.
+    s_file_stem: String,
+    naming: SaveNaming,
+    export: Export,
+    /// A save that would replace this file, waiting to be confirmed.
+    confirm_overwrite: Option<PathBuf>,
+    /// Where the last save went, or why it failed.
+    notice: Option<Result<PathBuf, String>>,
+}
+
+impl Default for SaveDialog
+{
+    fn default() -> Self
+    {
+        Self
+        {
+            s_file_stem: "my_image".to_string(),
+            naming: SaveNaming::default(),
+            export: Export::default(),
+            confirm_overwrite: None,
+            notice: None,
+        }
+    }
+}
+
+impl SaveDialog
+{
+    /// Where the next save goes.
+    fn path(&self, params: &FractalizeParameters) -> PathBuf
+    {
+        let extension = self.export.format.extension();
+        match self.naming
+        {
+            SaveNaming::AsTyped => PathBuf::from(format!("{}.{extension}", self.s_file_stem)),
+            SaveNaming::Increment => next_free_path(&self.s_file_stem, extension),
+            SaveNaming::Parameters => next_free_path(parameter_stem(params), extension),
+        }
+    }
+
+    fn save(&mut self, fractal_ew: &mut EventWriter<FractalEvent>, path: PathBuf)
+    {
+        self.confirm_overwrite = None;
+        self.notice = None;
+        fractal_ew.write(FractalEvent::Save { path, kind: SaveKind::Image(self.export) });
+    }
+}
+
+/// What a [`FractalEvent::Save`] writes.
+#[derive(Clone, Copy)]
+enum SaveKind
+{
+    /// The tonemapped image.
+    Image(Export),
+    /// The raw counts, which [`FractalEvent::LoadHistogram`] reads back.
+    Histogram,
+}
+
+/// A save encoding and writing on the [`AsyncComputeTaskPool`].
+#[derive(Component)]
+struct ComputeSave
+{
+    path: PathBuf,
+    kind: SaveKind,
+    task: Task<Result<(), FractalError>>,
+}
+
+/// Sent when a [`ComputeSave`] is done.
+#[derive(Event)]
+struct SaveFinished
+{
+    path: PathBuf,
+    kind: SaveKind,
+    result: Result<(), FractalError>,
+}
+
+fn handle_compute_save(
+    mut commands: Commands,
+    compute_save: Query<(Entity, &mut ComputeSave)>,
+    mut finished_ew: EventWriter<SaveFinished>,
+)
+{
+    for (ent, mut save) in compute_save
+    {
+        if let Some(result) = block_on(poll_once(&mut save.task))
+        {
+            commands.get_entity(ent).unwrap().despawn();
+            finished_ew.write(SaveFinished { path: save.path.clone(), kind: save.kind, result });
+        }
+    }
+}
+
+fn save_finished(
+    mut events: EventReader<SaveFinished>,
+    mut save_dialog: ResMut<SaveDialog>,
+    mut status: ResMut<FractalStatus>,
+)
+{
+    for SaveFinished { path, kind, result } in events.read()
+    {
+        // Histograms are saved from the settings window, away from the save panel.
+        if let SaveKind::Histogram = kind
+        {
+            match result
+            {
+                Ok(()) => println!("Saved histogram {}", path.display()),
+                Err(e) => status.report(&format!("Could not save {}", path.display()), e),
+            }
+            continue;
+        }
+
+        save_dialog.notice = Some(match result
+        {
+            Ok(()) =>
+            {
+                println!("Saved {}", path.display());
+                Ok(path.clone())
+            },
+            Err(e) =>
+            {
+                println!("Could not save {}: {e}", path.display());
+                Err(format!("Could not save {}: {e}", path.display()))
+            },
+        });
+    }
+}
+
+/// State of the "Explore" window.
+#[derive(Resource)]
+struct Explore
+{
+    search: RandomSearch,
+    found: Vec<Candidate>,
+    /// One per entry of `found`. Made by the gui, which has the egui context.
+    thumbnails: Vec<egui::TextureHandle>,
+}
+
+impl Default for Explore
+{
+    fn default() -> Self
+    {
+        Self
+        {
+            search: RandomSearch::default().with_candidates(128).with_top_k(12),
+            found: Vec::new(),
+            thumbnails: Vec::new(),
+        }
+    }
+}
+
+/// State of the "Map" window: a metric over all `rot` / `theta_offset` pairs.
+#[derive(Resource)]
+struct MapView
+{
+    map: ParameterMap,
+    /// Row-major, NaN where a row is still being computed.
+    values: Vec<f64>,
+    /// Bumped whenever the map is recomputed, so that rows of an older map are dropped.
+    generation: u32,
+    /// Set to stop the rows of the current map between cells; each map gets a new one.
+    cancel: Arc<AtomicBool>,
+    texture: Option<egui::TextureHandle>,
+    /// `values` changed since the texture was last uploaded.
+    dirty: bool,
+}
+
+impl Default for MapView
+{
+    fn default() -> Self
+    {
+        Self
+        {
+            map: ParameterMap::default(),
+            values: Vec::new(),
+            generation: 0,
+            cancel: Arc::new(AtomicBool::new(false)),
+            texture: None,
+            dirty: false,
+        }
+    }
+}
+
+impl MapView
+{
+    /// The texture contents: values scaled to the range computed so far,
+    /// pending cells in dark blue.
+    fn color_image(&self) -> egui::ColorImage
+    {
+        let finite = || self.values.iter().copied().filter(|v| v.is_finite());
+        let lo = finite().fold(f64::INFINITY, f64::min);
+        let hi = finite().fold(f64::NEG_INFINITY, f64::max);
+        let scale = if hi > lo { 1.0 / (hi - lo) } else { 0.0 };
+
+        let n = self.map.size as usize;
+        let rgba: Vec<u8> = self.values.iter()
+            .flat_map(|&v|
+            {
+                if v.is_nan()
+                {
+                    [0x14, 0x14, 0x28, 0xff]
+                }
+                else
+                {
+                    // -inf Lyapunov exponents show as black.
+                    let level = (((v - lo) * scale).clamp(0.0, 1.0) * 255.0) as u8;
+                    [level, level, level, 0xff]
+                }
+            })
+            .collect();
+
+        egui::ColorImage::from_rgba_unmultiplied([n, n], &rgba)
+    }
 }
 
 #[derive(Component)]
 struct FractalSprite;
 
-#[derive(Resource, Clone)]
+#[derive(Resource)]
 struct Fractal
 {
+    /// The tonemapped histogram, as displayed and saved.
     fractal: MyColorImage,
+    /// The histogram of the last finished render. Renders fill one of their own,
+    /// so this one stays whole when they are cancelled. Shared with a save in progress.
+    histogram: Arc<Histogram>,
+    /// `histogram` is from a preview, smaller and with fewer points than a full render, so it is not saved.
+    preview: bool,
+    /// Width and height of a full render.
+    resolution: UVec2,
     params: FractalizeParameters,
+    tonemap: Tonemap,
 }
 
 impl Fractal
 {
     /// An async implementation of the fractalize function.
     /// It can take a long time so it's good to make sure the rest of the app is running.
-    fn compute_fractalize_async(&self, thread_pool: &AsyncComputeTaskPool) -> ComputeFractal
+    /// Renders at `size` with `max_points` points; everything else comes from `self.params`.
+    /// The new histogram moves into the task and comes back with the result, so nothing is copied.
+    fn compute_fractalize_async(&self, thread_pool: &AsyncComputeTaskPool, generation: u64, size: UVec2, max_points: u32) -> ComputeFractal
     {
-        let mut frac = self.clone();
+        let mut histogram = Histogram::new(size.x, size.y);
+        let preview = (size, max_points) != (self.resolution, self.params.max_points);
+        let params = self.params.with_max_points(max_points);
+        let tonemap = self.tonemap;
+
+        let progress = Arc::new(RenderProgress::default());
+        let shared = progress.clone();
 
         let task = thread_pool.spawn(async move {
-            frac.fractal.fractalize(frac.params);
-            frac.fractal.pixels_mut().for_each(|p| p[3] = 0xff);
+            let mut last_partial = Instant::now();
+
+            histogram.fractalize_observed(params, DefaultBits::new(params.seed), |histogram, points|
+            {
+                shared.points.store(points, Ordering::Relaxed);
+
+                if last_partial.elapsed() >= RenderProgress::PARTIAL_INTERVAL
+                {
+                    *shared.partial.lock().unwrap() = Some(tonemap.apply(histogram));
+                    last_partial = Instant::now();
+                }
 
-            frac
+                // Dropping the task does not stop the loop, so it checks the flag between chunks of points.
+                if shared.cancel.load(Ordering::Relaxed) { ControlFlow::Break(()) } else { ControlFlow::Continue(()) }
+            })?;
+            let fractal = tonemap.apply(&histogram);
+
+            Ok(RenderOutput { histogram, fractal, tonemap })
         });
         
-        ComputeFractal { task }
+        ComputeFractal { generation, preview, started: Instant::now(), total_points: max_points, progress, task }
+    }
+}
+
+/// Renders a small preview while the sliders move, and the full render once
+/// they have been left alone for a while.
+#[derive(Resource)]
+struct AutoPreview
+{
+    enabled: bool,
+    /// The settings changed and no preview has been started for them yet.
+    preview_due: bool,
+    last_preview: Option<Instant>,
+    /// When the settings last changed, until the full render for them starts.
+    changed: Option<Instant>,
+}
+
+impl Default for AutoPreview
+{
+    fn default() -> Self
+    {
+        Self { enabled: true, preview_due: false, last_preview: None, changed: None }
+    }
+}
+
+impl AutoPreview
+{
+    /// Length of the long side of a preview.
+    const SIZE: u32 = 512;
+    const POINTS: u32 = 1_000_000;
+    /// At most one preview is started per interval while dragging.
+    const INTERVAL: Duration = Duration::from_millis(150);
+    /// How long the settings must stay unchanged before the full render starts.
+    const IDLE: Duration = Duration::from_millis(1500);
+
+    /// The preview size for a render at `resolution`, with the same aspect.
+    fn size(resolution: UVec2) -> UVec2
+    {
+        let long = resolution.max_element();
+        if long <= Self::SIZE
+        {
+            return resolution;
+        }
+        (resolution * Self::SIZE / long).max(UVec2::ONE)
+    }
+
+    fn settings_changed(&mut self)
+    {
+        if self.enabled
+        {
+            self.preview_due = true;
+            self.changed = Some(Instant::now());
+        }
+    }
+}
+
+fn auto_preview(
+    mut auto: ResMut<AutoPreview>,
+    mut fractal_ew: EventWriter<FractalEvent>,
+)
+{
+    if !auto.enabled
+    {
+        return;
+    }
+
+    if auto.preview_due && auto.last_preview.is_none_or(|t| t.elapsed() >= AutoPreview::INTERVAL)
+    {
+        fractal_ew.write(FractalEvent::Preview);
+        auto.preview_due = false;
+        auto.last_preview = Some(Instant::now());
+    }
+
+    if auto.changed.is_some_and(|t| t.elapsed() >= AutoPreview::IDLE)
+    {
+        fractal_ew.write(FractalEvent::Render);
+        auto.changed = None;
+    }
+}
+
+/// Hands out render generations. Only the render of the latest generation
+/// may replace the displayed fractal; older ones are cancelled.
+#[derive(Resource, Default)]
+struct RenderQueue
+{
+    generation: u64,
+}
+
+impl RenderQueue
+{
+    /// Cancels every running render and returns the generation for the next one.
+    fn supersede(&mut self, commands: &mut Commands, running: &Query<(Entity, &ComputeFractal)>) -> u64
+    {
+        for (ent, compute) in running
+        {
+            compute.progress.cancel.store(true, Ordering::Relaxed);
+            commands.entity(ent).despawn();
+        }
+
+        self.generation += 1;
+        self.generation
     }
 }
 
-/// A 
+/// A render running on the [`AsyncComputeTaskPool`].
 #[derive(Component)]
 struct ComputeFractal
 {
-    task: Task<Fractal>
+    generation: u64,
+    /// See [`Fractal::preview`].
+    preview: bool,
+    started: Instant,
+    total_points: u32,
+    progress: Arc<RenderProgress>,
+    task: Task<Result<RenderOutput, FractalError>>
+}
+
+/// What a finished render hands back to [`Fractal`].
+struct RenderOutput
+{
+    histogram: Histogram,
+    fractal: MyColorImage,
+    /// The tonemap `fractal` was made with.
+    tonemap: Tonemap,
+}
+
+/// Shared between a running render and the app.
+#[derive(Default)]
+struct RenderProgress
+{
+    /// Set to stop the render at its next chunk of points.
+    cancel: AtomicBool,
+    points: AtomicU64,
+    /// The latest tonemapped partial render, taken by the app for display.
+    partial: Mutex<Option<MyColorImage>>,
+}
+
+impl RenderProgress
+{
+    /// How often the render publishes a partial image.
+    const PARTIAL_INTERVAL: Duration = Duration::from_millis(300);
+}
+
+impl ComputeFractal
+{
+    /// Progress bar fraction and text: points done, rate and time left.
+    fn describe(&self) -> (f32, String)
+    {
+        let done = self.progress.points.load(Ordering::Relaxed);
+        let total = self.total_points as u64;
+        let elapsed = self.started.elapsed().as_secs_f64();
+
+        let rate = if elapsed > 0.0 { done as f64 / elapsed } else { 0.0 };
+        let eta = if rate > 0.0
+        {
+            format!("{:.0} s left", total.saturating_sub(done) as f64 / rate)
+        }
+        else
+        {
+            "starting".to_string()
+        };
+
+        let fraction = done as f32 / total.max(1) as f32;
+        (fraction, format!("{done} / {total} points, {:.1} M/s, {eta}", rate / 1e6))
+    }
 }
 
 fn handle_compute_fractal(
     mut commands: Commands,
     compute_fractal: Query<(Entity, &mut ComputeFractal)>,
+    queue: Res<RenderQueue>,
     mut fractal: ResMut<Fractal>,
     mut fractal_ew: EventWriter<FractalEvent>,
+    mut status: ResMut<FractalStatus>,
 )
 {
     for (ent, mut task) in compute_fractal
     {
+        if task.generation != queue.generation
+        {
+            // Superseded by a render requested in the same frame it was spawned.
+            task.progress.cancel.store(true, Ordering::Relaxed);
+            commands.get_entity(ent).unwrap().despawn();
+            continue;
+        }
+
+        if let Some(partial) = task.progress.partial.lock().unwrap().take()
+        {
+            fractal.fractal = partial;
+            fractal_ew.write(FractalEvent::Display);
+        }
+
         // if let Some(a) = block_on(poll_once(&mut task.task))
         if let Some(a) = block_on(poll_once(&mut task.task))
         {
-            let b = fractal.as_mut();
-            *b = a;
-
             commands.get_entity(ent).unwrap().despawn();
-            fractal_ew.write(FractalEvent::Display);
 
-            println!("Fractal rendering complete!!");
+            match a
+            {
+                Ok(a) =>
+                {
+                    let b = fractal.as_mut();
+                    // The tonemap may have changed while rendering.
+                    b.fractal = if a.tonemap == b.tonemap { a.fractal } else { b.tonemap.apply(&a.histogram) };
+                    b.histogram = Arc::new(a.histogram);
+                    b.preview = task.preview;
+
+                    fractal_ew.write(FractalEvent::Display);
+
+                    println!("Fractal rendering complete!!");
+                },
+                Err(FractalError::Cancelled) => println!("Fractal rendering cancelled"),
+                Err(e) => status.report("Fractal rendering failed", e),
+            }
         }
     }
 }
@@ -126,13 +656,20 @@ fn handle_compute_fractal(
 fn fractal_event(
     mut commands: Commands,
     mut events: EventReader<FractalEvent>,
+    mut queue: ResMut<RenderQueue>,
+    mut auto: ResMut<AutoPreview>,
+    running: Query<(Entity, &ComputeFractal)>,
     fractal_query: ResMut<Fractal>,
-    asset_server: Res<AssetServer>,
-    mut fractal_sprite: Option<Single<&mut Sprite, With<FractalSprite>>>,
+    mut images: ResMut<Assets<Image>>,
+    mut tiles: ResMut<FractalTiles>,
+    mut tile_sprites: Query<(&mut Sprite, &mut Transform, &mut Visibility), With<FractalTile>>,
+    mut settings_menu: ResMut<FractalSettingsMenu>,
+    mut status: ResMut<FractalStatus>,
 )
 {
     let thread_pool = AsyncComputeTaskPool::get();
     let fractal_query = fractal_query.into_inner();
+    let mut display = false;
 
     for event in events.read()
     {
@@ -140,8 +677,14 @@ fn fractal_event(
         {
             FractalEvent::Render => 
             {
-                fractal_query.fractal.pixels_mut().for_each(|p| { p[0] = 0; p[1] = 0; p[2] = 0; p[3] = 0; });
-                let compute_fractal = Fractal::compute_fractalize_async(fractal_query, thread_pool);
+                status.error = None;
+                // A render asked for by hand replaces the preview and the
+                // render waiting for the sliders to settle.
+                auto.preview_due = false;
+                auto.changed = None;
+                let generation = queue.supersede(&mut commands, &running);
+                let (resolution, max_points) = (fractal_query.resolution, fractal_query.params.max_points);
+                let compute_fractal = Fractal::compute_fractalize_async(fractal_query, thread_pool, generation, resolution, max_points);
                 commands.spawn(compute_fractal);
                 println!("Fractal rendering task created!");
 
@@ -149,90 +692,356 @@ fn fractal_event(
                 // fractal_query.fractal.pixels_mut().for_each(|p| p[3] = 0xff);
                 // println!("Render low done!")
             },
+            FractalEvent::Preview =>
+            {
+                let generation = queue.supersede(&mut commands, &running);
+                let size = AutoPreview::size(fractal_query.resolution);
+                let compute_fractal = Fractal::compute_fractalize_async(
+                    fractal_query, thread_pool, generation, size, AutoPreview::POINTS,
+                );
+                commands.spawn(compute_fractal);
+            },
+            FractalEvent::Cancel =>
+            {
+                auto.changed = None;
+                queue.supersede(&mut commands, &running);
+                // Back from the partial render to the last finished one.
+                fractal_query.fractal = fractal_query.tonemap.apply(&fractal_query.histogram);
+                display = true;
+            },
             FractalEvent::Settings(params) => 
             {
-                println!("Settings: {:?}", params);
-                fractal_query.params = params.clone();
+                // The gui systems run in no particular order, so the settings window can
+                // repeat settings that a click in the explore or map window sent along
+                // with a render in the same frame. Only a change may start a preview,
+                // which would supersede that render.
+                if *params != fractal_query.params
+                {
+                    println!("Settings: {:?}", params);
+                    fractal_query.params = params.clone();
+                    auto.settings_changed();
+                }
             },
-            FractalEvent::Display =>
+            FractalEvent::Tonemap(tonemap) =>
             {
-                println!("Display!");
-
-                let tf = TextureFormat::Rgba8Unorm;
-
-                let img = Image::new(
-                    Extent3d {
-                        depth_or_array_layers: 1,
-                        height: 4096,
-                        width: 4096,
-                    },
-                    bevy::render::render_resource::TextureDimension::D2,
-                    fractal_query.fractal.clone().into_vec(),
-                    tf,
-                    RenderAssetUsages::MAIN_WORLD | RenderAssetUsages::RENDER_WORLD,
-                );
-
-                let h = asset_server.add(img);
-
-                let mut sprite = Sprite::from_image(h);
-                sprite.anchor = Anchor::Center;
-                let mut transform = Transform::from_translation([0.0, 0.0, -1.0].into());
-                transform.scale = [1.0, 1.0, 1.0].into();
-
-                if let Some(spr) = fractal_sprite.take()
+                fractal_query.tonemap = *tonemap;
+                // A running render applies the new tonemap when done.
+                if running.is_empty()
                 {
-                    let spr = spr.into_inner().into_inner();
-                    *spr = sprite;
+                    fractal_query.fractal = tonemap.apply(&fractal_query.histogram);
+                    display = true;
                 }
-                else
+            },
+            FractalEvent::LoadHistogram(path) =>
+            {
+                match Histogram::load_raw(path)
                 {
-                    commands.spawn((
-                        FractalSprite,
-                        sprite,
-                        transform,
-                    ));
+                    Ok(histogram) =>
+                    {
+                        println!("Loaded histogram {}", path.display());
+
+                        // Later renders continue at the size of the loaded histogram.
+                        let resolution = UVec2::new(histogram.width(), histogram.height());
+                        fractal_query.resolution = resolution;
+                        settings_menu.resolution = resolution;
+                        fractal_query.fractal = fractal_query.tonemap.apply(&histogram);
+                        fractal_query.histogram = Arc::new(histogram);
+                        fractal_query.preview = false;
+                        display = true;
+                    },
+                    Err(e) => status.report(&format!("Could not load {}", path.display()), e),
                 }
             },
+            FractalEvent::Save { path, kind } =>
+            {
+                // Encoding a large image takes seconds, so it runs in the background
+                // on a shared copy of the histogram.
+                let (histogram, tonemap, kind, task_path) = (fractal_query.histogram.clone(), fractal_query.tonemap, *kind, path.clone());
+                let task = thread_pool.spawn(async move
+                {
+                    match kind
+                    {
+                        SaveKind::Image(export) => export.save(&histogram, &tonemap, &task_path),
+                        SaveKind::Histogram => histogram.save_raw(&task_path).map_err(FractalError::from),
+                    }
+                });
+                commands.spawn(ComputeSave { path: path.clone(), kind, task });
+                println!("Saving {}", path.display());
+            },
+            FractalEvent::Resolution(resolution) =>
+            {
+                println!("Resolution: {}x{}", resolution.x, resolution.y);
+                // A render at the old size would come back at the wrong aspect.
+                queue.supersede(&mut commands, &running);
+                fractal_query.resolution = *resolution;
+                fractal_query.fractal = MyColorImage::new(resolution.x, resolution.y);
+                fractal_query.histogram = Arc::new(Histogram::new(resolution.x, resolution.y));
+                fractal_query.preview = false;
+                auto.settings_changed();
+                display = true;
+            },
+            FractalEvent::Display =>
+            {
+                display = true;
+            },
         }
     }
+
+    if display
+    {
+        println!("Display!");
+        upload_fractal(&fractal_query.fractal, &mut tiles, &mut commands, &mut images, &mut tile_sprites);
+    }
 }
 
-fn fractal_gui(
-    mut contexts: EguiContexts,
-    mut fractal_ew: EventWriter<FractalEvent>,
-    settings_menu: ResMut<FractalSettingsMenu>,
-    fractal: Res<Fractal>,
-    rendering_fracs: Query<&ComputeFractal>,
-)
+/// The image is always [`WORLD_SIZE`] wide on screen, its height following its aspect.
+/// Previews are smaller than full renders but cover the same area.
+fn sprite_size(resolution: UVec2) -> Vec2
 {
-    let FractalSettingsMenu {fractal_method, f_theta_offset, f_rot, u_num_points} = settings_menu.into_inner();
+    Vec2::new(WORLD_SIZE, WORLD_SIZE * resolution.y as f32 / resolution.x as f32)
+}
 
-    egui::Window::new("Hello").show(
-        contexts.ctx_mut(), 
-        |ui|
-        {
-            let mut params = fractal.params.clone();
+/// The region of the plane shown at `zoom` around `(x, y)`: [-1, 1] across
+/// the width at zoom 1, the height following the aspect of `resolution`.
+fn view(x: f64, y: f64, zoom: f64, resolution: UVec2) -> Viewport
+{
+    let half_width = 1.0 / zoom;
+    let half_height = half_width * resolution.y as f64 / resolution.x as f64;
+    Viewport { x_min: x - half_width, x_max: x + half_width, y_min: y - half_height, y_max: y + half_height }
+}
 
-            ui.columns(2, 
-            |columns|
-            {
-                if columns[0].button("Render").clicked()
-                {
-                    fractal_ew.write(FractalEvent::Render);
-                }
-                columns[0].shrink_width_to_current();
+/// Largest side of one tile texture. Many GPUs cannot sample a larger 2D
+/// texture, so bigger images are split into a grid of tiles.
+const TILE_SIZE: u32 = 4096;
 
-                if rendering_fracs.iter().len() > 0
-                {
-                    columns[1].spinner();
-                }
+/// One tile of the fractal image, a child of the [`FractalSprite`].
+#[derive(Component)]
+struct FractalTile;
+
+/// The tile sprites, in the order of [`tile_layout`]. Tiles the current image
+/// does not need are hidden rather than despawned, so their textures are reused.
+#[derive(Resource)]
+struct FractalTiles
+{
+    parent: Entity,
+    tiles: Vec<(Entity, Handle<Image>)>,
+}
+
+/// The pixels of the fractal image one tile shows.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct TileRect
+{
+    origin: UVec2,
+    size: UVec2,
+}
+
+/// Splits an image of `size` into tiles of at most `tile_size` a side, row by
+/// row from the top left. The last column and row take what is left.
+fn tile_layout(size: UVec2, tile_size: u32) -> Vec<TileRect>
+{
+    let mut tiles = Vec::new();
+    for y in (0..size.y).step_by(tile_size as usize)
+    {
+        for x in (0..size.x).step_by(tile_size as usize)
+        {
+            let origin = UVec2::new(x, y);
+            tiles.push(TileRect { origin, size: (size - origin).min(UVec2::splat(tile_size)) });
+        }
+    }
+    tiles
+}
+
+impl TileRect
+{
+    /// Center and size in world units, relative to the [`FractalSprite`], of
+    /// this tile of an image of `size`. Image rows run down, world y runs up.
+    fn placement(self, size: UVec2) -> (Vec2, Vec2)
+    {
+        let extent = sprite_size(size);
+        let scale = extent.x / size.x as f32;
+        let center = (self.origin.as_vec2() + self.size.as_vec2() * 0.5) * scale;
+        (Vec2::new(center.x - extent.x * 0.5, extent.y * 0.5 - center.y), self.size.as_vec2() * scale)
+    }
+
+    /// This tile's rows of `fractal`, as RGBA bytes.
+    fn rows(self, fractal: &MyColorImage) -> impl Iterator<Item = &[u8]>
+    {
+        let stride = fractal.width() as usize * 4;
+        let start = self.origin.x as usize * 4;
+        let end = start + self.size.x as usize * 4;
+        fractal.as_raw()
+            .chunks_exact(stride)
+            .skip(self.origin.y as usize)
+            .take(self.size.y as usize)
+            .map(move |row| &row[start..end])
+    }
+}
+
+fn tile_image(fractal: &MyColorImage, rect: TileRect) -> Image
+{
+    let tf = TextureFormat::Rgba8Unorm;
+
+    Image::new(
+        Extent3d {
+            depth_or_array_layers: 1,
+            height: rect.size.y,
+            width: rect.size.x,
+        },
+        bevy::render::render_resource::TextureDimension::D2,
+        rect.rows(fractal).collect::<Vec<_>>().concat(),
+        tf,
+        RenderAssetUsages::MAIN_WORLD | RenderAssetUsages::RENDER_WORLD,
+    )
+}
+
+/// Shows `fractal` on the tile sprites, spawning tiles when it needs more than
+/// before. Only tiles whose pixels changed are written, since every write sends
+/// the whole texture to the GPU again.
+fn upload_fractal(
+    fractal: &MyColorImage,
+    tiles: &mut FractalTiles,
+    commands: &mut Commands,
+    images: &mut Assets<Image>,
+    tile_sprites: &mut Query<(&mut Sprite, &mut Transform, &mut Visibility), With<FractalTile>>,
+)
+{
+    let (width, height) = fractal.dimensions();
+    let size = UVec2::new(width, height);
+    let layout = tile_layout(size, TILE_SIZE);
+
+    for (i, &rect) in layout.iter().enumerate()
+    {
+        let (center, world_size) = rect.placement(size);
+        let transform = Transform::from_translation(center.extend(0.0));
+
+        let Some((entity, texture)) = tiles.tiles.get(i)
+        else
+        {
+            let texture = images.add(tile_image(fractal, rect));
+            let mut sprite = Sprite::from_image(texture.clone());
+            sprite.custom_size = Some(world_size);
+            let entity = commands.spawn((FractalTile, sprite, transform, ChildOf(tiles.parent))).id();
+            tiles.tiles.push((entity, texture));
+            continue;
+        };
+
+        if let Ok((mut sprite, mut tile_transform, mut visibility)) = tile_sprites.get_mut(*entity)
+        {
+            sprite.custom_size = Some(world_size);
+            *tile_transform = transform;
+            *visibility = Visibility::Inherited;
+        }
+
+        let row_len = rect.size.x as usize * 4;
+        let unchanged = images.get(texture).is_some_and(|img|
+        {
+            (img.width(), img.height()) == (rect.size.x, rect.size.y)
+                && img.data.as_ref().is_some_and(|data| data.chunks_exact(row_len).eq(rect.rows(fractal)))
+        });
+        if unchanged
+        {
+            continue;
+        }
+
+        let Some(img) = images.get_mut(texture)
+        else
+        {
+            continue;
+        };
+        if (img.width(), img.height()) != (rect.size.x, rect.size.y)
+        {
+            img.resize(Extent3d { width: rect.size.x, height: rect.size.y, depth_or_array_layers: 1 });
+        }
+        match &mut img.data
+        {
+            Some(data) =>
+            {
+                for (dst, src) in data.chunks_exact_mut(row_len).zip(rect.rows(fractal))
+                {
+                    dst.copy_from_slice(src);
+                }
+            },
+            None => img.data = Some(rect.rows(fractal).collect::<Vec<_>>().concat()),
+        }
+    }
+
+    for (entity, _) in &tiles.tiles[layout.len()..]
+    {
+        if let Ok((_, _, mut visibility)) = tile_sprites.get_mut(*entity)
+        {
+            *visibility = Visibility::Hidden;
+        }
+    }
+}
+
+fn fractal_gui(
+    mut contexts: EguiContexts,
+    mut fractal_ew: EventWriter<FractalEvent>,
+    settings_menu: ResMut<FractalSettingsMenu>,
+    fractal: Res<Fractal>,
+    rendering_fracs: Query<&ComputeFractal>,
+    mut status: ResMut<FractalStatus>,
+    mut auto: ResMut<AutoPreview>,
+    mut save_dialog: ResMut<SaveDialog>,
+    saving: Query<&ComputeSave>,
+)
+{
+    let FractalSettingsMenu {
+        fractal_method, f_theta_offset, f_rot, u_num_points,
+        precision, d_view_x, d_view_y, d_view_zoom,
+        symmetry, resolution, tonemap, s_histogram_path,
+    } = settings_menu.into_inner();
+
+    egui::Window::new("Hello").show(
+        contexts.ctx_mut(), 
+        |ui|
+        {
+            let mut params = fractal.params.clone();
+
+            ui.columns(2, 
+            |columns|
+            {
+                if columns[0].button("Render").clicked()
+                {
+                    fractal_ew.write(FractalEvent::Render);
+                }
+                columns[0].shrink_width_to_current();
+
+                if rendering_fracs.iter().len() > 0
+                {
+                    columns[1].horizontal(|ui|
+                    {
+                        ui.spinner();
+                        if ui.button("Cancel").clicked()
+                        {
+                            fractal_ew.write(FractalEvent::Cancel);
+                        }
+                    });
+                }
             });
 
-            if ui.button("Display").clicked()
+            for compute in &rendering_fracs
             {
-                fractal_ew.write(FractalEvent::Display);
+                let (fraction, text) = compute.describe();
+                ui.add(egui::ProgressBar::new(fraction).text(text));
             }
 
+            ui.horizontal(|ui|
+            {
+                if ui.button("Display").clicked()
+                {
+                    fractal_ew.write(FractalEvent::Display);
+                }
+
+                let auto_preview = ui.checkbox(&mut auto.enabled, "Auto preview")
+                    .on_hover_text("Preview while the settings change, and render once they settle");
+                if auto_preview.changed() && !auto.enabled
+                {
+                    auto.preview_due = false;
+                    auto.changed = None;
+                }
+            });
+
             ui.radio_value(fractal_method, FractalMethod::Default, "Default");
             ui.radio_value(fractal_method, FractalMethod::MultiplyTheta, "Multiply Theta");
 
@@ -245,10 +1054,77 @@ fn fractal_gui(
             let rot_slider = egui::Slider::new(f_rot, -PI..=PI);
             ui.add(rot_slider.text("Rot slider"));
 
+            ui.horizontal(|ui|
+            {
+                ui.add(egui::Slider::new(&mut symmetry.order, 1..=12).text("Symmetry"));
+                ui.checkbox(&mut symmetry.mirror, "Mirror");
+            });
+
+            ui.horizontal(|ui|
+            {
+                ui.radio_value(precision, Precision::F32, "f32");
+                ui.radio_value(precision, Precision::F64, "f64 (deep zoom)");
+            });
+
+            ui.horizontal(|ui|
+            {
+                let speed = 0.01 / *d_view_zoom;
+                ui.label("View center");
+                ui.add(egui::DragValue::new(d_view_x).speed(speed).max_decimals(12));
+                ui.add(egui::DragValue::new(d_view_y).speed(speed).max_decimals(12));
+            });
+
+            let view_zoom_slider = egui::Slider::new(d_view_zoom, 0.1..=1e9).logarithmic(true);
+            ui.add(view_zoom_slider.text("View zoom"));
+
+            // Reallocating is slow at large sizes, so dragging the width or
+            // height only applies once the drag ends.
+            let mut apply_resolution = false;
+            ui.horizontal(|ui|
+            {
+                ui.label("Resolution");
+                for preset in RESOLUTION_PRESETS
+                {
+                    apply_resolution |= ui.selectable_value(resolution, UVec2::splat(preset), preset.to_string()).clicked();
+                }
+            });
+            ui.horizontal(|ui|
+            {
+                for (side, label) in [(&mut resolution.x, "Width"), (&mut resolution.y, "Height")]
+                {
+                    ui.label(label);
+                    let edit = ui.add(egui::DragValue::new(side).range(RESOLUTION_RANGE));
+                    apply_resolution |= edit.drag_stopped() || edit.lost_focus();
+                }
+            });
+            if apply_resolution && *resolution != fractal.resolution
+            {
+                fractal_ew.write(FractalEvent::Resolution(*resolution));
+            }
+
             params.theta_offset = *f_theta_offset;
             params.rot = *f_rot;
             params.max_points = *u_num_points;
             params.method = *fractal_method;
+            params.precision = *precision;
+            params.viewport = view(*d_view_x, *d_view_y, *d_view_zoom, fractal.resolution);
+            params.symmetry = *symmetry;
+
+            if ui.button("Auto frame").clicked()
+            {
+                // A short pre-pass, quick enough to run in the ui.
+                let aspect = fractal.resolution.x as f64 / fractal.resolution.y as f64;
+                match Framing::default().with_aspect(aspect).fit(&params)
+                {
+                    Ok(viewport) =>
+                    {
+                        (*d_view_x, *d_view_y) = viewport.center();
+                        *d_view_zoom = 2.0 / viewport.width();
+                        params.viewport = view(*d_view_x, *d_view_y, *d_view_zoom, fractal.resolution);
+                    },
+                    Err(e) => status.report("Could not frame the attractor", e),
+                }
+            }
 
             if params != fractal.params
             {
@@ -257,12 +1133,837 @@ fn fractal_gui(
                 fractal_ew.write(FractalEvent::Settings(params));
             }
 
-            if ui.button("save image").clicked()
+            ui.separator();
+
+            let exposure_slider = egui::Slider::new(&mut tonemap.exposure, 0.01..=100.0).logarithmic(true);
+            ui.add(exposure_slider.text("Exposure"));
+
+            ui.horizontal(|ui|
+            {
+                ui.radio_value(&mut tonemap.curve, Curve::Linear, "Linear");
+                ui.radio_value(&mut tonemap.curve, Curve::Gamma, "Gamma");
+                ui.radio_value(&mut tonemap.curve, Curve::Log, "Log");
+            });
+
+            let gamma_slider = egui::Slider::new(&mut tonemap.gamma, 0.2..=5.0);
+            ui.add_enabled(tonemap.curve == Curve::Gamma, gamma_slider.text("Gamma"));
+
+            ui.horizontal(|ui|
+            {
+                ui.color_edit_button_srgb(&mut tonemap.low);
+                ui.color_edit_button_srgb(&mut tonemap.high);
+
+                if ui.button("Grayscale").clicked()
+                {
+                    (tonemap.low, tonemap.high) = (Tonemap::BLACK, Tonemap::WHITE);
+                }
+                if ui.button("Inverted").clicked()
+                {
+                    (tonemap.low, tonemap.high) = (Tonemap::WHITE, Tonemap::BLACK);
+                }
+            });
+
+            if *tonemap != fractal.tonemap
             {
-                let _ = fractal.fractal.save("my_image.png");
+                fractal_ew.write(FractalEvent::Tonemap(*tonemap));
+            }
+
+            ui.separator();
+
+            // The histogram is of the last finished render, not of the one shown while rendering.
+            let saveable = rendering_fracs.is_empty() && !fractal.preview;
+            ui.collapsing("Save image", |ui| save_panel(ui, &mut save_dialog, &fractal, saveable, &saving, &mut fractal_ew));
+
+            ui.text_edit_singleline(s_histogram_path);
+            ui.horizontal(|ui|
+            {
+                if ui.add_enabled(saveable, egui::Button::new("save histogram"))
+                    .on_disabled_hover_text("Wait for the full render to finish")
+                    .clicked()
+                {
+                    fractal_ew.write(FractalEvent::Save { path: PathBuf::from(s_histogram_path.as_str()), kind: SaveKind::Histogram });
+                }
+                if ui.button("load histogram").clicked()
+                {
+                    fractal_ew.write(FractalEvent::LoadHistogram(PathBuf::from(s_histogram_path.as_str())));
+                }
+            });
+
+            let mut dismissed = false;
+            if let Some(error) = &status.error
+            {
+                ui.separator();
+                ui.horizontal(|ui|
+                {
+                    ui.colored_label(egui::Color32::RED, error);
+                    dismissed = ui.small_button("x").clicked();
+                });
+            }
+            if dismissed
+            {
+                status.error = None;
             }
 
             
         }
     );
-}
\ No newline at end of file
+}
+
+fn save_panel(
+    ui: &mut egui::Ui,
+    dialog: &mut SaveDialog,
+    fractal: &Fractal,
+    saveable: bool,
+    saving: &Query<&ComputeSave>,
+    fractal_ew: &mut EventWriter<FractalEvent>,
+)
+{
+    ui.horizontal(|ui|
+    {
+        ui.label("File");
+        ui.add_enabled(dialog.naming != SaveNaming::Parameters, egui::TextEdit::singleline(&mut dialog.s_file_stem));
+    });
+
+    ui.horizontal(|ui|
+    {
+        ui.radio_value(&mut dialog.naming, SaveNaming::AsTyped, "As typed");
+        ui.radio_value(&mut dialog.naming, SaveNaming::Increment, "Numbered");
+        ui.radio_value(&mut dialog.naming, SaveNaming::Parameters, "From settings");
+    });
+
+    ui.horizontal(|ui|
+    {
+        for format in ExportFormat::ALL
+        {
+            ui.radio_value(&mut dialog.export.format, format, format.name());
+        }
+    });
+    if dialog.export.format == ExportFormat::Jpeg
+    {
+        ui.add(egui::Slider::new(&mut dialog.export.jpeg_quality, 1..=100).text("Quality"));
+    }
+
+    let path = dialog.path(&fractal.params);
+    ui.label(format!("Saves to {}", path.display()));
+
+    if let Some(existing) = dialog.confirm_overwrite.clone()
+    {
+        ui.horizontal(|ui|
+        {
+            ui.colored_label(egui::Color32::YELLOW, format!("{} exists. Replace it?", existing.display()));
+            if ui.button("Replace").clicked()
+            {
+                dialog.save(fractal_ew, existing);
+            }
+            if ui.button("Keep").clicked()
+            {
+                dialog.confirm_overwrite = None;
+            }
+        });
+    }
+    else if let Some(save) = saving.iter().next()
+    {
+        // One save at a time, so a numbered name is not handed out twice
+        // before the first file appears.
+        ui.horizontal(|ui|
+        {
+            ui.spinner();
+            ui.label(format!("Saving {}", save.path.display()));
+        });
+    }
+    else
+    {
+        let save = ui.add_enabled(saveable, egui::Button::new("Save"))
+            .on_disabled_hover_text("Wait for the full render to finish");
+        if save.clicked()
+        {
+            if path.exists()
+            {
+                dialog.confirm_overwrite = Some(path);
+            }
+            else
+            {
+                dialog.save(fractal_ew, path);
+            }
+        }
+    }
+
+    match &dialog.notice
+    {
+        Some(Ok(saved)) => { ui.colored_label(egui::Color32::GREEN, format!("Saved {}", saved.display())); },
+        Some(Err(error)) => { ui.colored_label(egui::Color32::RED, error); },
+        None => (),
+    }
+}
+
+/// A random search running in the background, see [`Explore`].
+#[derive(Component)]
+struct ComputeExplore
+{
+    task: Task<Result<Vec<Candidate>, FractalError>>
+}
+
+fn handle_compute_explore(
+    mut commands: Commands,
+    compute_explore: Query<(Entity, &mut ComputeExplore)>,
+    mut explore: ResMut<Explore>,
+    mut status: ResMut<FractalStatus>,
+)
+{
+    for (ent, mut task) in compute_explore
+    {
+        if let Some(found) = block_on(poll_once(&mut task.task))
+        {
+            commands.get_entity(ent).unwrap().despawn();
+
+            match found
+            {
+                Ok(found) =>
+                {
+                    println!("Explore found {} candidates", found.len());
+                    explore.found = found;
+                    explore.thumbnails.clear();
+                },
+                Err(e) => status.report("Explore failed", e),
+            }
+        }
+    }
+}
+
+fn explore_gui(
+    mut commands: Commands,
+    mut contexts: EguiContexts,
+    mut fractal_ew: EventWriter<FractalEvent>,
+    mut settings_menu: ResMut<FractalSettingsMenu>,
+    mut explore: ResMut<Explore>,
+    fractal: Res<Fractal>,
+    searching: Query<&ComputeExplore>,
+)
+{
+    let ctx = contexts.ctx_mut();
+    let explore = explore.as_mut();
+
+    if explore.thumbnails.len() != explore.found.len()
+    {
+        let tonemap = Tonemap::default().with_curve(Curve::Log);
+        explore.thumbnails = explore.found.iter()
+            .enumerate()
+            .map(|(i, candidate)|
+            {
+                let img = tonemap.apply(&candidate.preview);
+                let size = [img.width() as usize, img.height() as usize];
+                let img = egui::ColorImage::from_rgba_unmultiplied(size, img.as_raw());
+                ctx.load_texture(format!("explore_{i}"), img, egui::TextureOptions::LINEAR)
+            })
+            .collect();
+    }
+
+    egui::Window::new("Explore").show(
+        ctx,
+        |ui|
+        {
+            let search = &mut explore.search;
+
+            let candidates_slider = egui::Slider::new(&mut search.candidates, 8..=1024).logarithmic(true);
+            ui.add(candidates_slider.text("Candidates"));
+            ui.add(egui::Slider::new(&mut search.top_k, 1..=32).text("Keep best"));
+
+            ui.horizontal(|ui|
+            {
+                ui.label("Seed");
+                ui.add(egui::DragValue::new(&mut search.seed));
+            });
+
+            ui.horizontal(|ui|
+            {
+                let idle = searching.is_empty();
+                if ui.add_enabled(idle, egui::Button::new("Search")).clicked()
+                {
+                    // Everything but the angles comes from the current settings.
+                    let search = search.with_base(fractal.params);
+                    let task = AsyncComputeTaskPool::get().spawn(async move { search.run() });
+                    commands.spawn(ComputeExplore { task });
+                }
+                if !idle
+                {
+                    ui.spinner();
+                }
+            });
+
+            ui.separator();
+
+            const THUMBNAIL: f32 = 96.0;
+            const COLUMNS: usize = 4;
+
+            egui::Grid::new("explore_thumbnails").show(ui, |ui|
+            {
+                for (i, (candidate, thumbnail)) in explore.found.iter().zip(&explore.thumbnails).enumerate()
+                {
+                    let image = egui::Image::new(egui::load::SizedTexture::new(thumbnail.id(), [THUMBNAIL, THUMBNAIL]))
+                        .sense(egui::Sense::click());
+
+                    let m = candidate.metrics;
+                    let hover = format!(
+                        "rot {:.4}, theta_offset {:.4}\nscore {:.3}\ncoverage {:.3}, entropy {:.3}\ndimension {:.3}, lyapunov {:.3}",
+                        candidate.params.rot, candidate.params.theta_offset,
+                        candidate.score, m.coverage, m.entropy, m.dimension, m.lyapunov,
+                    );
+
+                    if ui.add(image).on_hover_text(hover).clicked()
+                    {
+                        settings_menu.f_rot = candidate.params.rot;
+                        settings_menu.f_theta_offset = candidate.params.theta_offset;
+
+                        let params = fractal.params
+                            .with_rot(candidate.params.rot)
+                            .with_theta_offset(candidate.params.theta_offset);
+                        fractal_ew.write(FractalEvent::Settings(params));
+                        fractal_ew.write(FractalEvent::Render);
+                    }
+
+                    if i % COLUMNS == COLUMNS - 1
+                    {
+                        ui.end_row();
+                    }
+                }
+            });
+        }
+    );
+}
+
+/// One row of the parameter map, see [`MapView`].
+#[derive(Component)]
+struct ComputeMapRow
+{
+    generation: u32,
+    row: u32,
+    task: Task<Result<Vec<f64>, FractalError>>
+}
+
+fn handle_compute_map_row(
+    mut commands: Commands,
+    compute_rows: Query<(Entity, &mut ComputeMapRow)>,
+    mut map_view: ResMut<MapView>,
+    mut status: ResMut<FractalStatus>,
+)
+{
+    for (ent, mut row) in compute_rows
+    {
+        if let Some(values) = block_on(poll_once(&mut row.task))
+        {
+            commands.get_entity(ent).unwrap().despawn();
+
+            if row.generation != map_view.generation
+            {
+                continue;
+            }
+
+            match values
+            {
+                Ok(values) =>
+                {
+                    let start = (row.row * map_view.map.size) as usize;
+                    map_view.values[start..start + values.len()].copy_from_slice(&values);
+                    map_view.dirty = true;
+                },
+                Err(e) => status.report("Map row failed", e),
+            }
+        }
+    }
+}
+
+fn map_gui(
+    mut commands: Commands,
+    mut contexts: EguiContexts,
+    mut fractal_ew: EventWriter<FractalEvent>,
+    mut settings_menu: ResMut<FractalSettingsMenu>,
+    mut map_view: ResMut<MapView>,
+    fractal: Res<Fractal>,
+    computing: Query<Entity, With<ComputeMapRow>>,
+)
+{
+    let ctx = contexts.ctx_mut();
+    let map_view = map_view.as_mut();
+
+    if map_view.dirty
+    {
+        let img = map_view.color_image();
+        match &mut map_view.texture
+        {
+            Some(texture) => texture.set(img, egui::TextureOptions::NEAREST),
+            None => map_view.texture = Some(ctx.load_texture("parameter_map", img, egui::TextureOptions::NEAREST)),
+        }
+        map_view.dirty = false;
+    }
+
+    egui::Window::new("Map").show(
+        ctx,
+        |ui|
+        {
+            ui.horizontal(|ui|
+            {
+                let metric = &mut map_view.map.metric;
+                ui.radio_value(metric, MapMetric::Coverage, "Coverage");
+                ui.radio_value(metric, MapMetric::Entropy, "Entropy");
+                ui.radio_value(metric, MapMetric::Dimension, "Dimension");
+                ui.radio_value(metric, MapMetric::Lyapunov, "Lyapunov");
+                ui.radio_value(metric, MapMetric::Score, "Score");
+            });
+
+            ui.horizontal(|ui|
+            {
+                if ui.button("Compute map").clicked()
+                {
+                    // Dropping the tasks of the previous map cancels the rows that have not
+                    // started. A running row does not stop when dropped, so it checks the flag.
+                    map_view.cancel.store(true, Ordering::Relaxed);
+                    map_view.cancel = Arc::new(AtomicBool::new(false));
+                    for ent in &computing
+                    {
+                        commands.entity(ent).despawn();
+                    }
+
+                    // Everything but the angles comes from the current settings.
+                    map_view.map.base = fractal.params;
+                    map_view.generation += 1;
+                    let n = map_view.map.size;
+                    map_view.values = vec![f64::NAN; (n * n) as usize];
+                    map_view.dirty = true;
+
+                    let thread_pool = AsyncComputeTaskPool::get();
+                    for row in 0..n
+                    {
+                        let (map, cancel) = (map_view.map, map_view.cancel.clone());
+                        let task = thread_pool.spawn(async move
+                        {
+                            (0..map.size)
+                                .map(|col| if cancel.load(Ordering::Relaxed) { Err(FractalError::Cancelled) } else { map.value(col, row) })
+                                .collect::<Result<Vec<f64>, FractalError>>()
+                        });
+                        commands.spawn(ComputeMapRow { generation: map_view.generation, row, task });
+                    }
+                }
+
+                let pending = computing.iter().len();
+                if pending > 0
+                {
+                    ui.spinner();
+                    ui.label(format!("{} of {} rows", map_view.map.size as usize - pending, map_view.map.size));
+                }
+            });
+
+            let Some(texture) = &map_view.texture
+            else
+            {
+                return;
+            };
+
+            let image = egui::Image::new(egui::load::SizedTexture::new(texture.id(), [256.0, 256.0]))
+                .sense(egui::Sense::click());
+            let response = ui.add(image).on_hover_text("rot across, theta_offset down");
+            let rect = response.rect;
+
+            // Mark the current settings.
+            let u = (settings_menu.f_rot + PI) / (2.0 * PI);
+            let v = (settings_menu.f_theta_offset + PI) / (2.0 * PI);
+            let at = rect.min + egui::vec2(u * rect.width(), v * rect.height());
+            ui.painter().circle_stroke(at, 4.0, egui::Stroke::new(1.5, egui::Color32::RED));
+
+            if let Some(pos) = response.clicked().then(|| response.interact_pointer_pos()).flatten()
+            {
+                let u = ((pos.x - rect.min.x) / rect.width()).clamp(0.0, 1.0);
+                let v = ((pos.y - rect.min.y) / rect.height()).clamp(0.0, 1.0);
+                let picked = map_view.map.params_at(u, v);
+
+                settings_menu.f_rot = picked.rot;
+                settings_menu.f_theta_offset = picked.theta_offset;
+
+                let params = fractal.params
+                    .with_rot(picked.rot)
+                    .with_theta_offset(picked.theta_offset);
+                fractal_ew.write(FractalEvent::Settings(params));
+                fractal_ew.write(FractalEvent::Render);
+            }
+        }
+    );
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::*;
+
+    /// The fractal plugin without its gui, which needs a window.
+    fn app() -> App
+    {
+        let mut app = App::new();
+        app
+        .add_plugins((MinimalPlugins, AssetPlugin::default()))
+        .init_asset::<Image>()
+        .add_event::<FractalEvent>()
+        .add_event::<SaveFinished>()
+        .init_resource::<FractalStatus>()
+        .init_resource::<RenderQueue>()
+        .init_resource::<AutoPreview>()
+        .init_resource::<SaveDialog>()
+        .add_systems(Startup, fractal_setup)
+        .add_systems(Update, (auto_preview, fractal_event, handle_compute_fractal, handle_compute_save, save_finished).chain())
+        ;
+        app.update();
+        app
+    }
+
+    fn image_count(app: &App) -> usize
+    {
+        app.world().resource::<Assets<Image>>().len()
+    }
+
+    /// Sizes of the textures of the tiles shown, in layout order.
+    fn tile_sizes(app: &App) -> Vec<(u32, u32)>
+    {
+        let world = app.world();
+        world.resource::<FractalTiles>().tiles.iter()
+            .filter(|(entity, _)| world.get::<Visibility>(*entity) != Some(&Visibility::Hidden))
+            .map(|(_, texture)|
+            {
+                let img = world.resource::<Assets<Image>>().get(texture).unwrap();
+                (img.width(), img.height())
+            })
+            .collect()
+    }
+
+    /// The area the shown tiles cover, in world units relative to the [`FractalSprite`].
+    fn tile_bounds(app: &App) -> Rect
+    {
+        let world = app.world();
+        world.resource::<FractalTiles>().tiles.iter()
+            .filter(|(entity, _)| world.get::<Visibility>(*entity) != Some(&Visibility::Hidden))
+            .map(|(entity, _)|
+            {
+                let center = world.get::<Transform>(*entity).unwrap().translation.truncate();
+                let size = world.get::<Sprite>(*entity).unwrap().custom_size.unwrap();
+                Rect::from_center_size(center, size)
+            })
+            .reduce(|a, b| a.union(b))
+            .unwrap()
+    }
+
+    fn show(app: &mut App, fractal: MyColorImage)
+    {
+        app.world_mut().resource_mut::<Fractal>().fractal = fractal;
+        app.world_mut().send_event(FractalEvent::Display);
+        app.update();
+    }
+
+    /// Updates until no task `C` is left.
+    fn wait_for<C: Component>(app: &mut App)
+    {
+        let start = Instant::now();
+        loop
+        {
+            app.update();
+
+            let mut running = app.world_mut().query::<&C>();
+            if running.iter(app.world()).next().is_none()
+            {
+                break;
+            }
+            assert!(start.elapsed() < Duration::from_secs(60), "task did not finish");
+            std::thread::sleep(Duration::from_millis(5));
+        }
+    }
+
+    fn render(app: &mut App)
+    {
+        app.world_mut().send_event(FractalEvent::Render);
+        wait_for::<ComputeFractal>(app);
+        // Display the result.
+        app.update();
+    }
+
+    #[test]
+    fn repeated_renders_reuse_one_texture()
+    {
+        let mut app = app();
+        let count = image_count(&app);
+
+        let params = app.world().resource::<Fractal>().params.with_max_points(64_000);
+        app.world_mut().send_event(FractalEvent::Settings(params));
+        for _ in 0..3
+        {
+            render(&mut app);
+            assert_eq!(image_count(&app), count);
+        }
+
+        for exposure in [2.0, 4.0]
+        {
+            app.world_mut().send_event(FractalEvent::Tonemap(Tonemap::default().with_exposure(exposure)));
+            app.update();
+        }
+
+        assert_eq!(image_count(&app), count);
+        assert_eq!(tile_sizes(&app), [(DEFAULT_RESOLUTION.x, DEFAULT_RESOLUTION.y)]);
+    }
+
+    #[test]
+    fn size_changes_resize_the_texture()
+    {
+        let mut app = app();
+        let count = image_count(&app);
+
+        for size in [AutoPreview::SIZE, DEFAULT_RESOLUTION.x, 100]
+        {
+            show(&mut app, MyColorImage::new(size, size));
+
+            assert_eq!(tile_sizes(&app), [(size, size)]);
+            assert_eq!(image_count(&app), count);
+        }
+    }
+
+    #[test]
+    fn resolution_keeps_the_world_width()
+    {
+        let mut app = app();
+        let count = image_count(&app);
+
+        for resolution in [UVec2::new(1024, 512), UVec2::new(300, 600), UVec2::splat(2048)]
+        {
+            app.world_mut().send_event(FractalEvent::Resolution(resolution));
+            app.update();
+
+            assert_eq!(tile_sizes(&app), [(resolution.x, resolution.y)]);
+            assert_eq!(image_count(&app), count);
+
+            let height = WORLD_SIZE * resolution.y as f32 / resolution.x as f32;
+            assert_eq!(tile_bounds(&app), Rect::from_center_size(Vec2::ZERO, Vec2::new(WORLD_SIZE, height)));
+        }
+    }
+
+    #[test]
+    fn tiles_cover_the_image()
+    {
+        for size in [UVec2::splat(100), UVec2::splat(TILE_SIZE), UVec2::new(10_000, 3000), UVec2::splat(16384)]
+        {
+            let layout = tile_layout(size, TILE_SIZE);
+            assert_eq!(layout.len() as u32, size.x.div_ceil(TILE_SIZE) * size.y.div_ceil(TILE_SIZE));
+
+            // Every pixel in exactly one tile, none outside the image.
+            let area: u64 = layout.iter().map(|t| t.size.x as u64 * t.size.y as u64).sum();
+            assert_eq!(area, size.x as u64 * size.y as u64);
+            for (i, a) in layout.iter().enumerate()
+            {
+                assert!(a.size.cmple(UVec2::splat(TILE_SIZE)).all() && a.size.cmpgt(UVec2::ZERO).all());
+                assert!((a.origin + a.size).cmple(size).all());
+                for b in &layout[i + 1..]
+                {
+                    let apart = (a.origin + a.size).cmple(b.origin).any() || (b.origin + b.size).cmple(a.origin).any();
+                    assert!(apart, "{a:?} overlaps {b:?}");
+                }
+            }
+        }
+
+        let layout = tile_layout(UVec2::new(10_000, 3000), TILE_SIZE);
+        assert_eq!(layout[2], TileRect { origin: UVec2::new(8192, 0), size: UVec2::new(1808, 3000) });
+    }
+
+    #[test]
+    fn tiles_are_placed_like_the_image()
+    {
+        let size = UVec2::new(10_000, 5000);
+        let layout = tile_layout(size, TILE_SIZE);
+        let scale = WORLD_SIZE / size.x as f32;
+
+        // The top left tile sits at the top left of the image, y up.
+        let (center, world_size) = layout[0].placement(size);
+        assert_eq!(world_size, Vec2::splat(TILE_SIZE as f32 * scale));
+        let top_left = center + Vec2::new(-world_size.x, world_size.y) * 0.5;
+        assert!(top_left.distance(Vec2::new(-WORLD_SIZE * 0.5, WORLD_SIZE * 0.25)) < 1e-2);
+
+        let bounds = layout.iter()
+            .map(|t| { let (c, s) = t.placement(size); Rect::from_center_size(c, s) })
+            .reduce(|a, b| a.union(b))
+            .unwrap();
+        assert!(bounds.min.distance(Vec2::new(-WORLD_SIZE * 0.5, -WORLD_SIZE * 0.25)) < 1e-2);
+        assert!(bounds.max.distance(Vec2::new(WORLD_SIZE * 0.5, WORLD_SIZE * 0.25)) < 1e-2);
+    }
+
+    #[test]
+    fn large_images_are_tiled()
+    {
+        let mut app = app();
+        let count = image_count(&app);
+        let size = UVec2::new(TILE_SIZE + 100, 64);
+
+        show(&mut app, MyColorImage::new(size.x, size.y));
+        assert_eq!(tile_sizes(&app), [(TILE_SIZE, 64), (100, 64)]);
+        assert_eq!(image_count(&app), count + 1);
+
+        // Back to one tile: the second is hidden and kept for later.
+        show(&mut app, MyColorImage::new(100, 100));
+        assert_eq!(tile_sizes(&app), [(100, 100)]);
+        show(&mut app, MyColorImage::new(size.x, size.y));
+        assert_eq!(image_count(&app), count + 1);
+    }
+
+    #[test]
+    fn only_changed_tiles_are_uploaded()
+    {
+        let mut app = app();
+        let size = UVec2::new(TILE_SIZE + 100, 64);
+        let mut fractal = MyColorImage::new(size.x, size.y);
+        show(&mut app, fractal.clone());
+
+        let modified = |app: &mut App, fractal: &MyColorImage|
+        {
+            let mut cursor = app.world().resource::<Events<AssetEvent<Image>>>().get_cursor_current();
+            show(app, fractal.clone());
+            // Asset events are sent at the start of the next frame.
+            app.update();
+
+            let events = app.world().resource::<Events<AssetEvent<Image>>>();
+            let mut ids: Vec<AssetId<Image>> = cursor.read(events)
+                .filter_map(|e| match e { AssetEvent::Modified { id } => Some(*id), _ => None })
+                .collect();
+            ids.dedup();
+            ids
+        };
+
+        assert_eq!(modified(&mut app, &fractal), []);
+
+        // One pixel in the second tile.
+        let i = (5 * size.x + TILE_SIZE + 10) as usize * 4;
+        (*fractal)[i] = 0xff;
+        let second = app.world().resource::<FractalTiles>().tiles[1].1.id();
+        assert_eq!(modified(&mut app, &fractal), [second]);
+    }
+
+    #[test]
+    fn previews_keep_the_aspect()
+    {
+        assert_eq!(AutoPreview::size(UVec2::splat(4096)), UVec2::splat(AutoPreview::SIZE));
+        assert_eq!(AutoPreview::size(UVec2::new(8192, 2048)), UVec2::new(512, 128));
+        assert_eq!(AutoPreview::size(UVec2::new(300, 200)), UVec2::new(300, 200));
+    }
+
+    #[test]
+    fn saves_run_in_the_background()
+    {
+        let mut app = app();
+        let params = app.world().resource::<Fractal>().params.with_max_points(64_000);
+        app.world_mut().send_event(FractalEvent::Settings(params));
+        render(&mut app);
+
+        let path = std::env::temp_dir().join(format!("rust_fractal_viewer_save_{}.png", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        app.world_mut().send_event(FractalEvent::Save { path: path.clone(), kind: SaveKind::Image(Export::default()) });
+
+        // A render started meanwhile gets its own histogram.
+        render(&mut app);
+        wait_for::<ComputeSave>(&mut app);
+
+        assert!(matches!(&app.world().resource::<SaveDialog>().notice, Some(Ok(saved)) if *saved == path));
+        assert!(path.exists());
+        std::fs::remove_file(&path).unwrap();
+
+        let missing = std::env::temp_dir().join("rust_fractal_viewer_missing").join("save.png");
+        app.world_mut().send_event(FractalEvent::Save { path: missing, kind: SaveKind::Image(Export::default()) });
+        wait_for::<ComputeSave>(&mut app);
+
+        assert!(matches!(app.world().resource::<SaveDialog>().notice, Some(Err(_))));
+    }
+
+    #[test]
+    fn histogram_saves_run_in_the_background()
+    {
+        let mut app = app();
+        let params = app.world().resource::<Fractal>().params.with_max_points(64_000);
+        app.world_mut().send_event(FractalEvent::Settings(params));
+        render(&mut app);
+
+        let path = std::env::temp_dir().join(format!("rust_fractal_viewer_save_{}.rfhist", std::process::id()));
+        app.world_mut().send_event(FractalEvent::Save { path: path.clone(), kind: SaveKind::Histogram });
+        wait_for::<ComputeSave>(&mut app);
+
+        let saved = Histogram::load_raw(&path).unwrap();
+        assert_eq!(saved.counts(), app.world().resource::<Fractal>().histogram.counts());
+        std::fs::remove_file(&path).unwrap();
+
+        let missing = std::env::temp_dir().join("rust_fractal_viewer_missing").join("save.rfhist");
+        app.world_mut().send_event(FractalEvent::Save { path: missing, kind: SaveKind::Histogram });
+        wait_for::<ComputeSave>(&mut app);
+
+        assert!(app.world().resource::<FractalStatus>().error.is_some());
+        // Image saves report in the save panel; this one does not.
+        assert!(app.world().resource::<SaveDialog>().notice.is_none());
+    }
+
+    #[test]
+    fn previews_are_marked()
+    {
+        let mut app = app();
+        // Only the previews asked for here.
+        app.world_mut().resource_mut::<AutoPreview>().enabled = false;
+        let params = app.world().resource::<Fractal>().params.with_max_points(64_000);
+        app.world_mut().send_event(FractalEvent::Settings(params));
+
+        app.world_mut().send_event(FractalEvent::Preview);
+        wait_for::<ComputeFractal>(&mut app);
+        let fractal = app.world().resource::<Fractal>();
+        assert!(fractal.preview);
+        assert_eq!(fractal.histogram.width(), AutoPreview::SIZE);
+
+        render(&mut app);
+        let fractal = app.world().resource::<Fractal>();
+        assert!(!fractal.preview);
+        assert_eq!(fractal.histogram.width(), DEFAULT_RESOLUTION.x);
+    }
+
+    #[test]
+    fn repeated_settings_do_not_preview_over_a_render()
+    {
+        let mut app = app();
+        let params = app.world().resource::<Fractal>().params.with_max_points(64_000);
+        app.world_mut().send_event(FractalEvent::Settings(params));
+        render(&mut app);
+
+        // A click renders, and the settings window repeats the settings in the same frame.
+        app.world_mut().send_event(FractalEvent::Render);
+        app.world_mut().send_event(FractalEvent::Settings(params));
+        app.update();
+
+        let auto = app.world().resource::<AutoPreview>();
+        assert!(!auto.preview_due && auto.changed.is_none());
+        wait_for::<ComputeFractal>(&mut app);
+    }
+
+    #[test]
+    fn cancelled_renders_keep_the_histogram()
+    {
+        let mut app = app();
+        let params = app.world().resource::<Fractal>().params.with_max_points(64_000);
+        app.world_mut().send_event(FractalEvent::Settings(params));
+        render(&mut app);
+        let rendered = app.world().resource::<Fractal>().histogram.counts().to_vec();
+
+        // Long enough to still be running when cancelled.
+        let params = params.with_max_points(u32::MAX);
+        app.world_mut().send_event(FractalEvent::Settings(params));
+        app.world_mut().send_event(FractalEvent::Render);
+        app.update();
+        app.world_mut().send_event(FractalEvent::Cancel);
+        wait_for::<ComputeFractal>(&mut app);
+
+        let fractal = app.world().resource::<Fractal>();
+        assert!(!fractal.histogram.counts().is_empty());
+        assert_eq!(fractal.histogram.counts(), rendered);
+
+        let path = std::env::temp_dir().join(format!("rust_fractal_viewer_cancelled_{}.png", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        app.world_mut().send_event(FractalEvent::Save { path: path.clone(), kind: SaveKind::Image(Export::default()) });
+        wait_for::<ComputeSave>(&mut app);
+
+        assert!(matches!(&app.world().resource::<SaveDialog>().notice, Some(Ok(saved)) if *saved == path));
+        assert!(path.exists());
+        std::fs::remove_file(&path).unwrap();
+    }
+}